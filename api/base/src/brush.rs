@@ -0,0 +1,80 @@
+use super::Color;
+
+// what an area is filled with: a solid color or a gradient
+#[derive(Debug, Clone, PartialEq)]
+pub enum Brush {
+	Color(Color),
+	// `angle` is in degrees, clockwise from the top as in css
+	Linear { angle: f32, stops: Vec<ColorStop> },
+	// an ellipse out to the farthest corner of the area
+	Radial { stops: Vec<ColorStop> },
+}
+
+// `position` is a fraction of the gradient line; stops without one are spread out evenly
+// between their neighbours, as in css
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColorStop {
+	pub color: Color,
+	pub position: Option<f32>,
+}
+
+impl Default for Brush {
+	fn default() -> Self {
+		Brush::Color(Color::default())
+	}
+}
+
+impl From<Color> for Brush {
+	fn from(color: Color) -> Self {
+		Brush::Color(color)
+	}
+}
+
+impl Brush {
+	pub fn stops(&self) -> &[ColorStop] {
+		match self {
+			Brush::Color(_) => &[],
+			Brush::Linear { stops, .. } | Brush::Radial { stops } => stops,
+		}
+	}
+
+	// where only a solid color can be drawn (text, borders, shadows) a gradient is drawn in
+	// the color of its first stop
+	pub fn fallback_color(&self) -> Color {
+		match self {
+			Brush::Color(color) => color.clone(),
+			_ => self.stops().first()
+				.map(|stop| stop.color.clone())
+				.unwrap_or_default(),
+		}
+	}
+
+	// the stops of a gradient with every position filled in and never decreasing
+	pub fn resolved_stops(&self) -> Vec<(f32, Color)> {
+		let stops = self.stops();
+		let mut positions: Vec<_> = stops.iter().map(|stop| stop.position).collect();
+		if let Some(first) = positions.first_mut() {
+			first.get_or_insert(0.0);
+		}
+		if let Some(last) = positions.last_mut() {
+			last.get_or_insert(1.0);
+		}
+		let mut start = 0;
+		for i in 1..positions.len() {
+			if let Some(end) = positions[i] {
+				let from = positions[start].unwrap();
+				let steps = (i - start) as f32;
+				for (j, position) in positions[start + 1..i].iter_mut().enumerate() {
+					*position = Some(from + (end - from) * (j + 1) as f32 / steps);
+				}
+				start = i;
+			}
+		}
+		let mut max = 0.0f32;
+		positions.into_iter().zip(stops).map(|(position, stop)| {
+			max = max.max(position.unwrap());
+			(max, stop.color.clone())
+		})
+		.collect()
+	}
+}
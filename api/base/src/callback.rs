@@ -9,6 +9,8 @@ enum CallbackInner<C: ComponentBase> {
 	Empty,
 	HostAbi(C::Abi),
 	Native(Box<dyn Fn(&mut C)>),
+	// a parent's callback handed down to a nested component
+	Forward(BoundCallback),
 }
 
 impl <C: ComponentBase> CallbackInner<C> {
@@ -16,7 +18,14 @@ impl <C: ComponentBase> CallbackInner<C> {
 		match self {
 			CallbackInner::Empty => {},
 			CallbackInner::HostAbi(abi) => abi.call(),
-			CallbackInner::Native(f) => f(&mut c.borrow_mut()),
+			// it may write to any prop, so all of them are marked, through the same borrow so that
+			// the mark can't be missed; the others write through setters that mark what they touch
+			CallbackInner::Native(f) => {
+				let mut c = c.borrow_mut();
+				f(&mut c);
+				c.dirty().mark_all();
+			},
+			CallbackInner::Forward(bound) => bound.call(),
 		}
 	}
 }
@@ -33,6 +42,7 @@ impl <C: ComponentBase> std::fmt::Debug for CallbackInner<C> {
 			CallbackInner::Empty      => write!(fmt, "Empty"),
 			CallbackInner::HostAbi(abi) => write!(fmt, "HostAbi({abi:?})"),
 			CallbackInner::Native(_)  => write!(fmt, "Native(Box<dyn Fn()>)"),
+			CallbackInner::Forward(bound) => write!(fmt, "Forward({bound:?})"),
 		}
 	}
 }
@@ -75,6 +85,9 @@ impl <C: ComponentBase> Callback<C> {
     pub fn from_abi(abi: C::Abi) -> Callback<C> {
         Callback(Rc::new(Cell::new(CallbackInner::HostAbi(abi))))
     }
+	pub fn forward(bound: BoundCallback) -> Callback<C> {
+		Callback(Rc::new(Cell::new(CallbackInner::Forward(bound))))
+	}
 }
 
 impl <C: ComponentBase + 'static> Callback<C> {
@@ -115,6 +128,7 @@ impl <C: ComponentBase + 'static> BoundCallbackTrait for BoundCallbackImpl<C> {
             CallbackInner::Empty => CallbackId::Empty,
             CallbackInner::HostAbi(f) => CallbackId::HostAbi(f.id()),
             CallbackInner::Native(f) => CallbackId::Native(unsafe { std::mem::transmute(f) }),
+            CallbackInner::Forward(bound) => bound.0.id(),
         };
         self.callback.0.set(c);
         result
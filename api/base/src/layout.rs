@@ -0,0 +1,304 @@
+use super::{ElementData, ElementImpl, GenericElement, Img, Length, Rect, Scroll};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Alignment {
+	Stretch,
+	Start,
+	Center,
+	End,
+}
+
+// placement of an element inside a row or column, set by the elements of the layout itself
+#[derive(Debug, Clone)]
+pub struct LayoutItem {
+	pub align: Alignment,
+	pub stretch: f32,
+}
+
+impl Default for LayoutItem {
+	fn default() -> Self {
+		LayoutItem {
+			align: Alignment::Stretch,
+			stretch: 1.0,
+		}
+	}
+}
+
+// a `row` or `column`: a `grow` layout is as long as its children along the main axis, a
+// `stretch` layout divides its own length between them according to their stretch factors
+#[derive(Debug, Clone)]
+pub struct Layout {
+	pub rect: Rect,
+	pub auto_width: bool,
+	pub auto_height: bool,
+	pub padding: Length,
+	pub spacing: Length,
+	pub column: bool,
+	pub grow: bool,
+}
+
+// the (main, cross) axis view of a size or position
+fn axes<T>(column: bool, (x, y): (T, T)) -> (T, T) {
+	if column {
+		(y, x)
+	} else {
+		(x, y)
+	}
+}
+
+fn size<D: ElementData>(e: &GenericElement<D>) -> (f32, f32) {
+	match &e.element_impl {
+		ElementImpl::Rect(rect) => (rect.bounds.width.to_px(), rect.bounds.height.to_px()),
+		ElementImpl::Layout(layout) => (layout.rect.bounds.width.to_px(), layout.rect.bounds.height.to_px()),
+		ElementImpl::Scroll(scroll) => (scroll.bounds.width.to_px(), scroll.bounds.height.to_px()),
+		ElementImpl::Img(img) => (img.bounds.width.to_px(), img.bounds.height.to_px()),
+		_ => (0.0, 0.0),
+	}
+}
+
+fn place<D: ElementData>(e: &mut GenericElement<D>, (x, y): (f32, f32), (width, height): (f32, f32)) {
+	match &mut e.element_impl {
+		ElementImpl::Rect(Rect { bounds, .. })
+		| ElementImpl::Layout(Layout { rect: Rect { bounds, .. }, .. })
+		| ElementImpl::Scroll(Scroll { bounds, .. })
+		| ElementImpl::Img(Img { bounds, .. }) => {
+			bounds.x = Length::Px(x);
+			bounds.y = Length::Px(y);
+			bounds.width = Length::Px(width);
+			bounds.height = Length::Px(height);
+		},
+		ElementImpl::Span(span) => {
+			span.x = Length::Px(x);
+			span.y = Length::Px(y);
+		},
+		_ => {},
+	}
+}
+
+// groups (repeaters, nested components) are transparent to the layout; an item set on the
+// group applies to everything inside it
+fn collect_items<'a, D: ElementData>(
+	children: &'a mut [GenericElement<D>],
+	inherited: Option<&LayoutItem>,
+	items: &mut Vec<(&'a mut GenericElement<D>, LayoutItem)>,
+) {
+	for e in children.iter_mut() {
+		if !e.show {
+			continue;
+		}
+		let item = e.layout_item.clone().or_else(|| inherited.cloned());
+		if let ElementImpl::Group = e.element_impl {
+			collect_items(&mut e.children, item.as_ref(), items);
+		} else {
+			items.push((e, item.unwrap_or_default()));
+		}
+	}
+}
+
+// computes the bounds of everything inside a row or column: a bottom-up pass fits `grow` and
+// auto-sized layouts to their children, then a top-down pass places the children of every
+// layout inside the size it ended up with
+pub fn arrange<D: ElementData>(e: &mut GenericElement<D>) {
+	measure(e);
+	place_children(e);
+}
+
+fn measure<D: ElementData>(e: &mut GenericElement<D>) {
+	for child in e.children.iter_mut() {
+		measure(child);
+	}
+
+	let layout = match &mut e.element_impl {
+		ElementImpl::Layout(layout) => layout,
+		_ => return,
+	};
+
+	let column = layout.column;
+	let (auto_main, auto_cross) = axes(column, (layout.auto_width, layout.auto_height));
+	if !layout.grow && !auto_main && !auto_cross {
+		return;
+	}
+	// children are placed inside the border, so it only adds to a size fitted to them
+	let border = layout.rect.border_width();
+	let padding = layout.padding.to_px();
+	let spacing = layout.spacing.to_px();
+	let mut items = Vec::new();
+	collect_items(&mut e.children, None, &mut items);
+
+	let sizes: Vec<_> = items.iter().map(|(e, _)| axes(column, size(e))).collect();
+	let gaps = spacing * items.len().saturating_sub(1) as f32;
+	let content_main = sizes.iter().map(|e| e.0).sum::<f32>() + gaps;
+	let content_cross = sizes.iter().map(|e| e.1).fold(0.0, f32::max);
+
+	let bounds = &mut layout.rect.bounds;
+	let (main, cross) = axes(column, (&mut bounds.width, &mut bounds.height));
+	if layout.grow || auto_main {
+		*main = Length::Px(content_main + (padding + border) * 2.0);
+	}
+	if auto_cross {
+		*cross = Length::Px(content_cross + (padding + border) * 2.0);
+	}
+}
+
+fn place_children<D: ElementData>(e: &mut GenericElement<D>) {
+	if let ElementImpl::Layout(layout) = &e.element_impl {
+		let column = layout.column;
+		let grow = layout.grow;
+		let border = layout.rect.border_width();
+		let padding = layout.padding.to_px();
+		let spacing = layout.spacing.to_px();
+		let (main_size, cross_size) = axes(column, (layout.rect.bounds.width.to_px(), layout.rect.bounds.height.to_px()));
+		let mut items = Vec::new();
+		collect_items(&mut e.children, None, &mut items);
+
+		let sizes: Vec<_> = items.iter().map(|(e, _)| axes(column, size(e))).collect();
+		let gaps = spacing * items.len().saturating_sub(1) as f32;
+		let inner_cross = (cross_size - (padding + border) * 2.0).max(0.0);
+		let available = (main_size - (padding + border) * 2.0 - gaps).max(0.0);
+		let total_stretch: f32 = items.iter().map(|(_, item)| item.stretch.max(0.0)).sum();
+
+		let mut position = padding;
+		for ((child, item), (own_main, own_cross)) in items.into_iter().zip(sizes) {
+			let child_main = if grow || total_stretch <= 0.0 {
+				own_main
+			} else {
+				available * item.stretch.max(0.0) / total_stretch
+			};
+			let (child_cross, offset) = match item.align {
+				Alignment::Stretch => (inner_cross, 0.0),
+				Alignment::Start => (own_cross, 0.0),
+				Alignment::Center => (own_cross, (inner_cross - own_cross) / 2.0),
+				Alignment::End => (own_cross, inner_cross - own_cross),
+			};
+			let origin = axes(column, (position, padding + offset));
+			let size = axes(column, (child_main, child_cross));
+			place(child, origin, size);
+			position += child_main + spacing;
+		}
+	}
+
+	for child in e.children.iter_mut() {
+		place_children(child);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{Border, Bounds, Shadow};
+
+	#[derive(Default, Debug)]
+	struct TestData;
+
+	impl ElementData for TestData {}
+
+	fn rect_impl(width: f32, height: f32) -> Rect {
+		Rect {
+			color: Default::default(),
+			bounds: Bounds {
+				x: Length::Px(0.0),
+				y: Length::Px(0.0),
+				width: Length::Px(width),
+				height: Length::Px(height),
+			},
+			border: Border { width: Length::Px(0.0), color: Default::default() },
+			clip: false,
+			radius: Length::Px(0.0),
+			shadow: Shadow {
+				x: Length::Px(0.0),
+				y: Length::Px(0.0),
+				blur: Length::Px(0.0),
+				color: Default::default(),
+			},
+			opacity: 1.0,
+		}
+	}
+
+	fn rect(width: f32, height: f32) -> GenericElement<TestData> {
+		GenericElement::new(ElementImpl::Rect(rect_impl(width, height)))
+	}
+
+	fn layout(column: bool, grow: bool, padding: f32, spacing: f32, children: Vec<GenericElement<TestData>>) -> GenericElement<TestData> {
+		let mut e = GenericElement::new(ElementImpl::Layout(Layout {
+			rect: rect_impl(0.0, 0.0),
+			auto_width: true,
+			auto_height: true,
+			padding: Length::Px(padding),
+			spacing: Length::Px(spacing),
+			column,
+			grow,
+		}));
+		e.children = children;
+		e
+	}
+
+	fn with_item(mut e: GenericElement<TestData>, align: Alignment, stretch: f32) -> GenericElement<TestData> {
+		e.layout_item = Some(LayoutItem { align, stretch });
+		e
+	}
+
+	fn bounds(e: &GenericElement<TestData>) -> (f32, f32, f32, f32) {
+		let (width, height) = size(e);
+		match &e.element_impl {
+			ElementImpl::Rect(Rect { bounds, .. }) | ElementImpl::Layout(Layout { rect: Rect { bounds, .. }, .. }) => {
+				(bounds.x.to_px(), bounds.y.to_px(), width, height)
+			},
+			_ => unreachable!(),
+		}
+	}
+
+	#[test]
+	fn grow_layouts_fit_their_children() {
+		let mut row = layout(false, true, 5.0, 10.0, vec![rect(10.0, 20.0), with_item(rect(30.0, 40.0), Alignment::End, 1.0)]);
+		arrange(&mut row);
+		assert_eq!(bounds(&row), (0.0, 0.0, 60.0, 50.0));
+		// stretched across the row by default
+		assert_eq!(bounds(&row.children[0]), (5.0, 5.0, 10.0, 40.0));
+		assert_eq!(bounds(&row.children[1]), (25.0, 5.0, 30.0, 40.0));
+	}
+
+	#[test]
+	fn stretch_layouts_divide_their_length() {
+		let mut column = layout(true, false, 0.0, 0.0, vec![
+			with_item(rect(10.0, 0.0), Alignment::Center, 1.0),
+			with_item(rect(10.0, 0.0), Alignment::Start, 3.0),
+		]);
+		if let ElementImpl::Layout(layout) = &mut column.element_impl {
+			layout.auto_height = false;
+			layout.rect.bounds.height = Length::Px(100.0);
+			layout.auto_width = false;
+			layout.rect.bounds.width = Length::Px(50.0);
+		}
+		arrange(&mut column);
+		assert_eq!(bounds(&column.children[0]), (20.0, 0.0, 10.0, 25.0));
+		assert_eq!(bounds(&column.children[1]), (0.0, 25.0, 10.0, 75.0));
+	}
+
+	#[test]
+	fn groups_and_hidden_elements_are_skipped() {
+		let mut hidden = rect(100.0, 100.0);
+		hidden.show = false;
+		let mut group = GenericElement::new(ElementImpl::Group);
+		group.children = vec![rect(10.0, 10.0), rect(20.0, 10.0)];
+		let mut row = layout(false, true, 0.0, 0.0, vec![hidden, group]);
+		arrange(&mut row);
+		assert_eq!(bounds(&row), (0.0, 0.0, 30.0, 10.0));
+		assert_eq!(bounds(&row.children[1].children[1]), (10.0, 0.0, 20.0, 10.0));
+	}
+
+	#[test]
+	fn nested_layouts_are_arranged_in_one_pass_each() {
+		// each level used to arrange everything below it twice, which took minutes at this depth
+		let mut e = rect(10.0, 10.0);
+		for _ in 0..40 {
+			e = layout(true, true, 1.0, 0.0, vec![e]);
+		}
+		arrange(&mut e);
+		assert_eq!(bounds(&e), (0.0, 0.0, 90.0, 90.0));
+		let mut inner = &e;
+		while !inner.children.is_empty() {
+			inner = &inner.children[0];
+		}
+		assert_eq!(bounds(inner), (1.0, 1.0, 10.0, 10.0));
+	}
+}
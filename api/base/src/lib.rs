@@ -1,9 +1,19 @@
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 
+mod brush;
 mod callback;
+mod color;
+mod layout;
+mod scroll;
+mod units;
 
+pub use brush::{Brush, ColorStop};
 pub use callback::{Callback, BoundCallback};
+pub use color::Color;
+pub use layout::{Alignment, Layout, LayoutItem, arrange};
+pub use scroll::{Scroll, ScrollBinding};
+pub use units::{LengthContext, resolve_lengths};
 
 #[derive(Debug, Clone)]
 pub struct RawBounds {
@@ -51,20 +61,20 @@ impl Bounds {
 	}
 }
 
-#[derive(Debug, Clone)]
-pub struct Color {
-	pub r: u8,
-	pub g: u8,
-	pub b: u8,
-	pub a: f32,
-}
-
+// `Percent`, `Em`, `Vw` and `Vh` are relative and only known once resolved against a
+// `LengthContext`
 #[derive(Debug, Clone)]
 pub enum Length {
 	Px(f32),
 	In(f32),
 	Cm(f32),
 	Mm(f32),
+	Pt(f32),
+	Percent(f32),
+	Em(f32),
+	Vw(f32),
+	Vh(f32),
+	Calc(Calc),
 }
 
 impl Default for Length {
@@ -73,77 +83,314 @@ impl Default for Length {
 	}
 }
 
+// a sum of lengths in different units, like css `calc()`; absolute units are folded into `px`
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Calc {
+	pub px: f32,
+	pub percent: f32,
+	pub em: f32,
+	pub vw: f32,
+	pub vh: f32,
+}
+
+impl Calc {
+	fn map(&self, f: impl Fn(f32) -> f32) -> Calc {
+		Calc {
+			px: f(self.px),
+			percent: f(self.percent),
+			em: f(self.em),
+			vw: f(self.vw),
+			vh: f(self.vh),
+		}
+	}
+
+	fn zip(&self, other: &Calc, f: impl Fn(f32, f32) -> f32) -> Calc {
+		Calc {
+			px: f(self.px, other.px),
+			percent: f(self.percent, other.percent),
+			em: f(self.em, other.em),
+			vw: f(self.vw, other.vw),
+			vh: f(self.vh, other.vh),
+		}
+	}
+
+	// back to a plain length when at most one term is left
+	fn simplify(self) -> Length {
+		let terms = [self.px, self.percent, self.em, self.vw, self.vh];
+		match terms.iter().filter(|n| **n != 0.0).count() {
+			0 => Length::Px(0.0),
+			1 if self.px != 0.0 => Length::Px(self.px),
+			1 if self.percent != 0.0 => Length::Percent(self.percent),
+			1 if self.em != 0.0 => Length::Em(self.em),
+			1 if self.vw != 0.0 => Length::Vw(self.vw),
+			1 => Length::Vh(self.vh),
+			_ => Length::Calc(self),
+		}
+	}
+}
+
 impl Length {
+	// absolute units as in css, at 96px to the inch; relative lengths that haven't been resolved
+	// count as 0
 	pub fn to_px(&self) -> f32 {
 		match *self {
 			Length::Px(f) => f,
-			_ => unimplemented!()
+			Length::In(f) => f * 96.0,
+			Length::Cm(f) => f * 96.0 / 2.54,
+			Length::Mm(f) => f * 96.0 / 25.4,
+			Length::Pt(f) => f * 96.0 / 72.0,
+			Length::Percent(_) | Length::Em(_) | Length::Vw(_) | Length::Vh(_) => 0.0,
+			Length::Calc(ref calc) => calc.px,
 		}
 	}
+
+	pub fn is_relative(&self) -> bool {
+		matches!(self, Length::Percent(_) | Length::Em(_) | Length::Vw(_) | Length::Vh(_) | Length::Calc(_))
+	}
+
+	fn to_calc(&self) -> Calc {
+		match *self {
+			Length::Percent(percent) => Calc { percent, ..Calc::default() },
+			Length::Em(em) => Calc { em, ..Calc::default() },
+			Length::Vw(vw) => Calc { vw, ..Calc::default() },
+			Length::Vh(vh) => Calc { vh, ..Calc::default() },
+			Length::Calc(ref calc) => calc.clone(),
+			_ => Calc { px: self.to_px(), ..Calc::default() },
+		}
+	}
+
+	// the same length with its amount changed, in whatever unit it is in
+	fn map(self, f: impl Fn(f32) -> f32) -> Length {
+		match self {
+			Length::Px(n) => Length::Px(f(n)),
+			Length::In(n) => Length::In(f(n)),
+			Length::Cm(n) => Length::Cm(f(n)),
+			Length::Mm(n) => Length::Mm(f(n)),
+			Length::Pt(n) => Length::Pt(f(n)),
+			Length::Percent(n) => Length::Percent(f(n)),
+			Length::Em(n) => Length::Em(f(n)),
+			Length::Vw(n) => Length::Vw(f(n)),
+			Length::Vh(n) => Length::Vh(f(n)),
+			Length::Calc(calc) => Length::Calc(calc.map(f)),
+		}
+	}
+
+	// adds or subtracts lengths, keeping the unit when both are in the same one
+	fn combine(self, rhs: Length, f: impl Fn(f32, f32) -> f32) -> Length {
+		match (&self, &rhs) {
+			(Length::Px(a), Length::Px(b)) => Length::Px(f(*a, *b)),
+			(Length::In(a), Length::In(b)) => Length::In(f(*a, *b)),
+			(Length::Cm(a), Length::Cm(b)) => Length::Cm(f(*a, *b)),
+			(Length::Mm(a), Length::Mm(b)) => Length::Mm(f(*a, *b)),
+			(Length::Pt(a), Length::Pt(b)) => Length::Pt(f(*a, *b)),
+			(Length::Percent(a), Length::Percent(b)) => Length::Percent(f(*a, *b)),
+			(Length::Em(a), Length::Em(b)) => Length::Em(f(*a, *b)),
+			(Length::Vw(a), Length::Vw(b)) => Length::Vw(f(*a, *b)),
+			(Length::Vh(a), Length::Vh(b)) => Length::Vh(f(*a, *b)),
+			_ => self.to_calc().zip(&rhs.to_calc(), f).simplify(),
+		}
+	}
+}
+
+impl PartialEq for Length {
+	fn eq(&self, other: &Length) -> bool {
+		self.partial_cmp(other) == Some(std::cmp::Ordering::Equal)
+	}
 }
 
+// absolute lengths compare by their size in px whatever their unit; a relative length is only
+// known once resolved, so it is equal to the same length and unordered against anything else
+impl PartialOrd for Length {
+	fn partial_cmp(&self, other: &Length) -> Option<std::cmp::Ordering> {
+		if self.is_relative() || other.is_relative() {
+			(self.to_calc() == other.to_calc()).then_some(std::cmp::Ordering::Equal)
+		} else {
+			self.to_px().partial_cmp(&other.to_px())
+		}
+	}
+}
+
+impl std::ops::Add for Length {
+	type Output = Length;
+	fn add(self, rhs: Length) -> Length {
+		self.combine(rhs, |a, b| a + b)
+	}
+}
+
+impl std::ops::Sub for Length {
+	type Output = Length;
+	fn sub(self, rhs: Length) -> Length {
+		self.combine(rhs, |a, b| a - b)
+	}
+}
+
+impl std::ops::Mul<f32> for Length {
+	type Output = Length;
+	fn mul(self, rhs: f32) -> Length {
+		self.map(|n| n * rhs)
+	}
+}
+
+impl std::ops::Mul<Length> for f32 {
+	type Output = Length;
+	fn mul(self, rhs: Length) -> Length {
+		rhs * self
+	}
+}
+
+impl std::ops::Div<f32> for Length {
+	type Output = Length;
+	fn div(self, rhs: f32) -> Length {
+		self.map(|n| n / rhs)
+	}
+}
+
+impl std::ops::Neg for Length {
+	type Output = Length;
+	fn neg(self) -> Length {
+		self.map(|n| -n)
+	}
+}
+
+// a list prop; edits made through it are recorded as changes, so that what renders it can tell
+// which items changed. A list that is built or cloned starts out with all of its items inserted,
+// since whatever renders it can't have seen them yet
 #[derive(Debug)]
-pub enum Iterable<T> {
-	Int(i32),
-	Array(Vec<T>),
+pub struct Iterable<T> {
+	items: Vec<T>,
+	changes: Vec<Change>,
+}
+
+// one edit made to an `Iterable`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change {
+	// the item at `index` was replaced
+	Set(usize),
+	// `removed` items from `index` on were replaced by `inserted` new ones
+	Splice {
+		index: usize,
+		removed: usize,
+		inserted: usize,
+	},
 }
 
 impl <T> Default for Iterable<T> {
 	fn default() -> Self {
-		Iterable::Int(0)
+		Iterable {
+			items: Vec::new(),
+			changes: Vec::new(),
+		}
+	}
+}
+
+impl <T> From<Vec<T>> for Iterable<T> {
+	fn from(items: Vec<T>) -> Self {
+		let changes = match items.len() {
+			0 => Vec::new(),
+			inserted => vec![Change::Splice { index: 0, removed: 0, inserted }],
+		};
+		Iterable { items, changes }
+	}
+}
+
+impl <T: Clone> Clone for Iterable<T> {
+	fn clone(&self) -> Self {
+		self.items.clone().into()
 	}
 }
 
 impl <T> Iterable<T> {
 	pub fn from<U: Clone + Into<T>, A: AsRef<[U]>>(array: A) -> Self {
-		let vector = array.as_ref().iter().map(|e| e.clone().into()).collect();
-		Iterable::Array(vector)
+		let vector: Vec<T> = array.as_ref().iter().map(|e| e.clone().into()).collect();
+		vector.into()
+	}
+	pub fn len(&self) -> usize {
+		self.items.len()
+	}
+	pub fn is_empty(&self) -> bool {
+		self.items.is_empty()
+	}
+	pub fn as_slice(&self) -> &[T] {
+		&self.items
 	}
 	pub fn set_index(&mut self, i: usize, value: T) {
-		match self {
-			Iterable::Int(_) => {},
-			Iterable::Array(a) => {
-				if i < a.len() {
-					a[i] = value;
-				}
-			}
+		if let Some(item) = self.items.get_mut(i) {
+			*item = value;
+			self.changes.push(Change::Set(i));
 		}
 	}
-	pub fn len(&self) -> usize {
-		match self {
-			Iterable::Int(n) => std::cmp::max(*n, 0) as usize,
-			Iterable::Array(a) => a.len()
+	pub fn push(&mut self, value: T) {
+		let index = self.items.len();
+		self.splice(index, 0, std::iter::once(value));
+	}
+	// past the end, the item is appended
+	pub fn insert(&mut self, i: usize, value: T) {
+		self.splice(i, 0, std::iter::once(value));
+	}
+	pub fn remove(&mut self, i: usize) -> Option<T> {
+		self.splice(i, 1, std::iter::empty()).pop()
+	}
+	pub fn clear(&mut self) {
+		self.splice(0, self.items.len(), std::iter::empty());
+	}
+	// like `Vec::splice`, but `index` and `removed` are clamped to the items there are;
+	// returns the removed items
+	pub fn splice<I: IntoIterator<Item = T>>(&mut self, index: usize, removed: usize, items: I) -> Vec<T> {
+		let index = index.min(self.items.len());
+		let end = index + removed.min(self.items.len() - index);
+		let len = self.items.len();
+		let removed: Vec<T> = self.items.splice(index..end, items).collect();
+		let inserted = self.items.len() + removed.len() - len;
+		if !removed.is_empty() || inserted > 0 {
+			self.changes.push(Change::Splice { index, removed: removed.len(), inserted });
 		}
+		removed
+	}
+	// the edits made since the last call, oldest first
+	pub fn take_changes(&mut self) -> Vec<Change> {
+		std::mem::take(&mut self.changes)
 	}
 }
 
-impl Iterable<String> {
-	pub fn iter<'a>(&'a self) -> Box<dyn std::iter::Iterator<Item = String> + 'a> {
-		match self {
-			Iterable::Int(n) => Box::new((0..*n).map(|e| e.to_string())),
-			Iterable::Array(a) => Box::new(a.iter().cloned())
-		}
-	}
-	pub fn get_index(&self, i: usize) -> String {
-		match self {
-			Iterable::Int(n) => if (i as i32) < *n { (i as i32).to_string() } else { 0.to_string() },
-			Iterable::Array(a) => a.get(i).map(|e| e.clone()).unwrap_or_default(),
+// which of the `len` items a list holds now were changed by `changes`, for a repeater over it.
+// Around a splice, the items of a keyed repeater keep their elements while those of any other
+// shift into the elements of their neighbours
+pub fn changed_rows(changes: &[Change], len: usize, keyed: bool) -> Vec<bool> {
+	let added: isize = changes.iter()
+		.map(|change| match *change {
+			Change::Set(_) => 0,
+			Change::Splice { removed, inserted, .. } => inserted as isize - removed as isize,
+		})
+		.sum();
+	let mut rows = vec![false; (len as isize - added).max(0) as usize];
+	for change in changes {
+		match *change {
+			Change::Set(i) => {
+				if let Some(row) = rows.get_mut(i) {
+					*row = true;
+				}
+			},
+			Change::Splice { index, removed, inserted } => {
+				let index = index.min(rows.len());
+				let end = (index + removed).min(rows.len());
+				rows.splice(index..end, std::iter::repeat_n(true, inserted));
+				if !keyed && removed != inserted {
+					rows[index..].fill(true);
+				}
+			},
 		}
 	}
+	rows.resize(len, true);
+	rows
 }
 
-impl Iterable<i32> {
-	pub fn iter<'a>(&'a self) -> Box<dyn std::iter::Iterator<Item = i32> + 'a> {
-		match self {
-			Iterable::Int(n) => Box::new(0..*n),
-			Iterable::Array(a) => Box::new(a.iter().copied())
-		}
+impl <T: Clone + Default> Iterable<T> {
+	pub fn iter(&self) -> impl std::iter::Iterator<Item = T> + '_ {
+		self.items.iter().cloned()
 	}
-	pub fn get_index(&self, i: usize) -> i32 {
-		match self {
-			Iterable::Int(n) => if (i as i32) < *n { i as i32 } else { 0 },
-			Iterable::Array(a) => a.get(i).map(|e| e.clone()).unwrap_or_default(),
-		}
+	// out of range, the default value stands in for the item
+	pub fn get_index(&self, i: usize) -> T {
+		self.items.get(i).cloned().unwrap_or_default()
 	}
 }
 
@@ -163,9 +410,46 @@ impl Convert<String> for i32 {
 	}
 }
 
+impl Convert<String> for f32 {
+	fn convert(&self) -> String {
+		self.to_string()
+	}
+}
+
+impl Convert<String> for bool {
+	fn convert(&self) -> String {
+		self.to_string()
+	}
+}
+
+impl Convert<f32> for i32 {
+	fn convert(&self) -> f32 {
+		*self as f32
+	}
+}
+
 pub trait ComponentBase: std::fmt::Debug + Component {
 	type Abi: HostAbi;
 	fn update<D: ElementData>(this: Rc<RefCell<Self>>, parent: &mut GenericElement<D>);
+	fn dirty(&self) -> &Dirty;
+}
+
+// the props written since the last update, one bit per prop in the order of their names, with
+// the last bit shared by any past the 63rd; the generated `set_<prop>`/`<prop>_mut` methods and
+// callbacks mark what they touch, and the edits recorded by a list count as changes to it
+#[derive(Debug, Default)]
+pub struct Dirty(Cell<u64>);
+
+impl Dirty {
+	pub fn mark(&self, props: u64) {
+		self.0.set(self.0.get() | props);
+	}
+	pub fn mark_all(&self) {
+		self.0.set(u64::MAX);
+	}
+	pub fn take(&self) -> u64 {
+		self.0.replace(0)
+	}
 }
 
 pub trait Component: Default {
@@ -179,11 +463,15 @@ pub trait DefaultProps: Default {
 	}
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum ElementImpl {
 	Root(f32, f32),
 	Group,
+	Window(Window),
 	Rect(Rect),
+	Layout(Layout),
+	Scroll(Scroll),
+	Img(Img),
 	Span(Span),
 	Text(Text),
 }
@@ -194,6 +482,15 @@ impl ElementImpl {
 			ElementImpl::Rect(rect) => {
 				Some(rect.bounds.to_px_bounds())
 			},
+			ElementImpl::Layout(layout) => {
+				Some(layout.rect.bounds.to_px_bounds())
+			},
+			ElementImpl::Scroll(scroll) => {
+				Some(scroll.bounds.to_px_bounds())
+			},
+			ElementImpl::Img(img) => {
+				Some(img.bounds.to_px_bounds())
+			},
 			&ElementImpl::Root(w, h) => {
 				Some(PxBounds { x: 0.0, y: 0.0, width: w, height: h })
 			},
@@ -202,36 +499,179 @@ impl ElementImpl {
 	}
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Rect {
-	pub color: Color,
+	pub color: Brush,
 	pub bounds: Bounds,
+	pub border: Border,
+	// whether children are cut off at the inner edge of the border
+	pub clip: bool,
+	pub radius: Length,
+	pub shadow: Shadow,
+	// applies to the children as well
+	pub opacity: f32,
 }
 
-#[derive(Debug)]
+impl Rect {
+	// the children of a rect are placed inside its border, as in css
+	pub fn border_width(&self) -> f32 {
+		self.border.width.to_px().max(0.0)
+	}
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Border {
+	pub width: Length,
+	pub color: Brush,
+}
+
+// a transparent color means no shadow
+#[derive(Debug, Clone, PartialEq)]
+pub struct Shadow {
+	pub x: Length,
+	pub y: Length,
+	pub blur: Length,
+	pub color: Brush,
+}
+
+// the settings of the native window an app's root element opens; it covers the whole window
+// and so has no bounds of its own
+#[derive(Debug, Clone, PartialEq)]
+pub struct Window {
+	pub title: String,
+	pub width: Length,
+	pub height: Length,
+	pub min_width: Option<Length>,
+	pub min_height: Option<Length>,
+	pub max_width: Option<Length>,
+	pub max_height: Option<Length>,
+	pub resizable: bool,
+}
+
+// how an image is scaled into its bounds: whole and centered, covering them and cropped, or
+// stretched to them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Fit {
+	Contain,
+	Cover,
+	#[default]
+	Fill,
+}
+
+#[derive(Debug, Clone)]
+pub struct Img {
+	pub bounds: Bounds,
+	// a file path on native, a URL on the web
+	pub src: String,
+	pub fit: Fit,
+}
+
+#[derive(Debug, Clone)]
 pub struct Span {
 	pub max_width: Option<f32>,
 	pub x: Length,
 	pub y: Length,
-	pub color: Color,
+	pub color: Brush,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Text {
 	pub content: String,
 }
 
-pub trait ElementData: Default {}
+pub trait ElementData: Default + 'static {}
+
+// renders the child elements a component was instantiated with, in the scope of the
+// component that instantiated it; the filter holds the element names a slot accepts
+pub struct Slot<D: ElementData>(Option<Rc<SlotFn<D>>>);
+
+type SlotFn<D> = dyn Fn(&mut GenericElement<D>, &[&str]);
+
+impl <D: ElementData> Slot<D> {
+	pub fn new<F: Fn(&mut GenericElement<D>, &[&str]) + 'static>(f: F) -> Self {
+		Slot(Some(Rc::new(f)))
+	}
+	pub fn render(&self, parent: &mut GenericElement<D>, filter: &[&str]) {
+		if let Some(f) = self.0.as_ref() {
+			f(parent, filter);
+		}
+	}
+}
+
+impl <D: ElementData> Default for Slot<D> {
+	fn default() -> Self {
+		Slot(None)
+	}
+}
+
+impl <D: ElementData> Clone for Slot<D> {
+	fn clone(&self) -> Self {
+		Slot(self.0.clone())
+	}
+}
+
+impl <D: ElementData> std::fmt::Debug for Slot<D> {
+	fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+		match self.0 {
+			Some(_) => write!(fmt, "Slot(..)"),
+			None => write!(fmt, "Slot(None)"),
+		}
+	}
+}
+
+// the state of a nested component, owned by the element it renders into
+#[derive(Default)]
+pub struct Instance(Option<Rc<dyn std::any::Any>>);
+
+impl std::fmt::Debug for Instance {
+	fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+		match self.0 {
+			Some(_) => write!(fmt, "Instance(..)"),
+			None => write!(fmt, "Instance(None)"),
+		}
+	}
+}
+
+// identifies an item of a keyed repeater across updates
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Key {
+	Int(i32),
+	String(String),
+}
+
+impl From<i32> for Key {
+	fn from(key: i32) -> Self {
+		Key::Int(key)
+	}
+}
+
+impl From<String> for Key {
+	fn from(key: String) -> Self {
+		Key::String(key)
+	}
+}
+
+impl From<&str> for Key {
+	fn from(key: &str) -> Self {
+		Key::String(key.to_string())
+	}
+}
 
 #[derive(Debug)]
 pub struct GenericElement<D: ElementData> {
 	id: usize,
+	key: Option<Key>,
 	pub data: D,
 	pub element_impl: ElementImpl,
 	pub children: Vec<GenericElement<D>>,
 	pub show: bool,
 	pub group: bool,
 	pub events: Events,
+	pub layout_item: Option<LayoutItem>,
+	// the element as it was built, before layout resolved its lengths and bounds
+	source: Option<ElementImpl>,
+	instance: Instance,
+	slot: Slot<D>,
 }
 
 fn next_id() -> usize {
@@ -247,24 +687,34 @@ impl <D: ElementData> GenericElement<D> {
 	pub fn root() -> Self {
 		GenericElement {
 			id: next_id(),
+			key: None,
 			element_impl: ElementImpl::Root(0.0, 0.0),
 			children: Vec::new(),
 			show: true,
 			group: false,
 			events: Default::default(),
+			layout_item: None,
+			source: None,
 			data: Default::default(),
+			instance: Default::default(),
+			slot: Default::default(),
 		}
 	}
 
 	pub fn new(e: ElementImpl) -> Self {
 		GenericElement {
 			id: next_id(),
+			key: None,
+			source: Some(e.clone()),
 			element_impl: e,
 			children: Vec::new(),
 			show: true,
 			group: false,
 			events: Default::default(),
+			layout_item: None,
 			data: Default::default(),
+			instance: Default::default(),
+			slot: Default::default(),
 		}
 	}
 
@@ -272,10 +722,14 @@ impl <D: ElementData> GenericElement<D> {
 		self.id
 	}
 
-	pub fn element_in(&mut self, e: ElementImpl, i: usize) -> &mut Self {
+	pub fn element_in(&mut self, mut e: ElementImpl, i: usize) -> &mut Self {
 		if i < self.children.len() {
 			let element = &mut self.children[i];
 			element.show = true;
+			if let (ElementImpl::Scroll(scroll), ElementImpl::Scroll(previous)) = (&mut e, &element.element_impl) {
+				scroll.keep_offset(previous);
+			}
+			element.source = Some(e.clone());
 			element.element_impl = e;
 			element
 		} else if i == self.children.len() {
@@ -286,8 +740,116 @@ impl <D: ElementData> GenericElement<D> {
 		}
 	}
 
+	// like `element_in`, but takes the child with `key` from those not placed yet, so that it
+	// moves to `i` along with everything it holds
+	pub fn keyed_element_in(&mut self, e: ElementImpl, i: usize, key: Key) -> &mut Self {
+		match self.children[i..].iter().position(|c| c.key.as_ref() == Some(&key)) {
+			Some(0) => {},
+			Some(j) => {
+				let element = self.children.remove(i + j);
+				self.children.insert(i, element);
+			},
+			None => {
+				let mut element = GenericElement::new(e);
+				element.key = Some(key);
+				self.children.insert(i, element);
+				return &mut self.children[i];
+			},
+		}
+		self.element_in(e, i)
+	}
+
+	// like `element_in`, but an element that is shown and hasn't `changed` is restored instead of
+	// being built again
+	pub fn element_in_with<F: FnOnce() -> ElementImpl>(&mut self, i: usize, changed: bool, build: F) -> &mut Self {
+		match self.children.get_mut(i) {
+			Some(element) if element.show && !changed => {
+				element.restore_impl();
+				&mut self.children[i]
+			},
+			_ => self.element_in(build(), i),
+		}
+	}
+
+	// restores the child at `i` along with everything below it, for a subtree none of whose
+	// bindings changed; false if there is no such child shown yet
+	pub fn restore_in(&mut self, i: usize) -> bool {
+		match self.children.get_mut(i) {
+			Some(element) if element.show => {
+				element.restore();
+				true
+			},
+			_ => false,
+		}
+	}
+
+	// like `restore_in`, but first moves the child with `key` to `i` as `keyed_element_in` would;
+	// false if there is no such child yet
+	pub fn restore_keyed_in(&mut self, i: usize, key: &Key) -> bool {
+		match self.children[i..].iter().position(|c| c.key.as_ref() == Some(key)) {
+			Some(j) => {
+				let element = self.children.remove(i + j);
+				self.children.insert(i, element);
+				self.restore_in(i)
+			},
+			None => false,
+		}
+	}
+
+	fn restore(&mut self) {
+		self.restore_impl();
+		for e in self.children.iter_mut() {
+			e.restore();
+		}
+	}
+
+	// layout writes its results into the element, so they are undone before it runs again
+	fn restore_impl(&mut self) {
+		if let Some(source) = self.source.as_ref() {
+			let mut e = source.clone();
+			if let (ElementImpl::Scroll(scroll), ElementImpl::Scroll(previous)) = (&mut e, &self.element_impl) {
+				scroll.keep_offset(previous);
+			}
+			self.element_impl = e;
+		}
+	}
+
 	pub fn element_out(&mut self, e: ElementImpl, i: usize) {
-		self.element_in(e, i).show = false;
+		self.element_in(e, i).hide();
+	}
+
+	// hides the element along with everything below it; nested components are dropped so that
+	// they start over from their defaults when shown again
+	fn hide(&mut self) {
+		self.show = false;
+		self.instance = Instance::default();
+		for e in self.children.iter_mut() {
+			e.hide();
+		}
+	}
+
+	pub fn slot(&self) -> Slot<D> {
+		self.slot.clone()
+	}
+
+	pub fn set_slot(&mut self, slot: Slot<D>) {
+		self.slot = slot;
+	}
+
+	pub fn has_instance(&self) -> bool {
+		self.instance.0.is_some()
+	}
+
+	// returns the component rendered into this element, creating it with `init` on first use
+	pub fn instance_in<C: 'static, F: FnOnce() -> C>(&mut self, init: F) -> Rc<RefCell<C>> {
+		self.group = true;
+		let existing = self.instance.0.clone().and_then(|e| e.downcast::<RefCell<C>>().ok());
+		if let Some(instance) = existing {
+			return instance;
+		}
+		let instance = Rc::new(RefCell::new(init()));
+		self.instance = Instance(Some(instance.clone()));
+		instance
 	}
 
 	pub fn begin_group(&mut self, i: usize) -> &mut Self {
@@ -298,10 +860,15 @@ impl <D: ElementData> GenericElement<D> {
 
 	pub fn end_group(&mut self, i: usize) {
 		for e in self.children.iter_mut().skip(i) {
-			e.show = false;
+			e.hide();
 		}
 	}
 
+	// drops the children past `i`, whose keys are no longer in the collection
+	pub fn end_keyed_group(&mut self, i: usize) {
+		self.children.truncate(i);
+	}
+
 	pub fn handle_event<C: ComponentBase + 'static>(&mut self, component: Rc<RefCell<C>>, event_type: EventType, callback: Option<Callback<C>>) {
 		match event_type {
 			EventType::PointerClick   => self.events.pointer_click   = callback.map(|c| c.bind(&component)),
@@ -310,6 +877,7 @@ impl <D: ElementData> GenericElement<D> {
 			EventType::PointerMove    => self.events.pointer_move    = callback.map(|c| c.bind(&component)),
 			EventType::PointerIn      => self.events.pointer_in      = callback.map(|c| c.bind(&component)),
 			EventType::PointerOut     => self.events.pointer_out     = callback.map(|c| c.bind(&component)),
+			EventType::Scroll         => self.events.scroll          = callback.map(|c| c.bind(&component)),
 		}
 	}
 }
@@ -333,6 +901,7 @@ pub enum EventType {
 	PointerMove,
 	PointerIn,
 	PointerOut,
+	Scroll,
 }
 
 #[derive(Debug)]
@@ -343,6 +912,7 @@ pub struct Events  {
 	pub pointer_move: Option<BoundCallback>,
 	pub pointer_in: Option<BoundCallback>,
 	pub pointer_out: Option<BoundCallback>,
+	pub scroll: Option<BoundCallback>,
 }
 
 impl Default for Events {
@@ -354,7 +924,259 @@ impl Default for Events {
 			pointer_move: None,
 			pointer_in: None,
 			pointer_out: None,
+			scroll: None,
 		}
 	}
 }
 
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn lengths_in_the_same_unit_keep_it() {
+		assert_eq!(Length::Percent(10.0) + Length::Percent(15.0), Length::Percent(25.0));
+		assert_eq!(Length::Em(2.0) - Length::Em(0.5), Length::Em(1.5));
+		assert!(matches!(Length::Cm(1.0) + Length::Cm(2.0), Length::Cm(n) if n == 3.0));
+	}
+
+	#[test]
+	fn mixed_units_make_a_calc() {
+		let length = Length::Px(10.0) + Length::Percent(50.0) - Length::Vw(5.0);
+		assert!(matches!(length, Length::Calc(Calc { px, percent, vw, .. }) if px == 10.0 && percent == 50.0 && vw == -5.0));
+		// absolute units are folded into px
+		assert_eq!(Length::In(1.0) + Length::Pt(72.0), Length::Px(192.0));
+	}
+
+	#[test]
+	fn calc_simplifies_to_its_last_term() {
+		let length = Length::Percent(10.0) + Length::Px(5.0) - Length::Px(5.0);
+		assert!(matches!(length, Length::Percent(n) if n == 10.0));
+		let length = Length::Em(1.0) - Length::Em(1.0) + Length::Vh(3.0) - Length::Percent(0.0);
+		assert!(matches!(length, Length::Vh(n) if n == 3.0));
+		assert!(matches!(Length::Vw(1.0) - Length::Vw(1.0), Length::Vw(n) if n == 0.0));
+		let calc = Length::Px(5.0) + Length::Em(1.0);
+		assert!(matches!(calc.clone() - Length::Em(1.0), Length::Px(n) if n == 5.0));
+		assert!(matches!(calc.clone() - calc, Length::Px(n) if n == 0.0));
+	}
+
+	#[test]
+	fn scaling_and_negation_apply_to_every_term() {
+		let length = (Length::Px(10.0) + Length::Percent(20.0)) * 2.0;
+		assert!(matches!(length, Length::Calc(Calc { px, percent, .. }) if px == 20.0 && percent == 40.0));
+		assert_eq!(0.5 * Length::Px(10.0), Length::Px(5.0));
+		assert_eq!(Length::Percent(30.0) / 3.0, Length::Percent(10.0));
+		assert_eq!(-Length::Mm(1.0), Length::Mm(-1.0));
+	}
+
+	#[test]
+	fn calc_resolves_each_term() {
+		let ctx = LengthContext { width: 200.0, height: 100.0, font_size: 10.0, viewport_width: 1000.0, viewport_height: 500.0 };
+		let length = Length::Px(1.0) + Length::Percent(50.0) + Length::Em(2.0) + Length::Vw(1.0) + Length::Vh(2.0);
+		assert_eq!(length.resolve(&ctx, ctx.height), 1.0 + 50.0 + 20.0 + 10.0 + 10.0);
+	}
+
+	#[test]
+	fn absolute_lengths_compare_across_units() {
+		assert_eq!(Length::In(1.0), Length::Px(96.0));
+		assert!(Length::Cm(1.0) > Length::Mm(9.0));
+		assert!(Length::Pt(72.0) <= Length::In(1.0));
+	}
+
+	#[test]
+	fn relative_lengths_are_unordered() {
+		assert_eq!(Length::Percent(50.0).partial_cmp(&Length::Px(0.0)), None);
+		assert_ne!(Length::Percent(50.0), Length::Px(0.0));
+		assert_eq!(Length::Em(1.0).partial_cmp(&Length::Px(100.0)), None);
+		assert_eq!(Length::Vh(1.0).partial_cmp(&Length::Vw(1.0)), None);
+		assert_eq!(Length::Vw(5.0), Length::Vw(5.0));
+		assert_eq!(Length::Percent(50.0), Length::Calc(Calc { percent: 50.0, ..Calc::default() }));
+	}
+
+	fn list(items: &[&str]) -> Iterable<String> {
+		let mut list = Iterable::from(items);
+		list.take_changes();
+		list
+	}
+
+	#[test]
+	fn splice_clamps_its_range() {
+		let mut l = list(&["a", "b", "c"]);
+		assert!(l.splice(10, 2, [String::from("d")]).is_empty());
+		assert_eq!(l.take_changes(), vec![Change::Splice { index: 3, removed: 0, inserted: 1 }]);
+		assert_eq!(l.splice(2, 10, []), vec!["c", "d"]);
+		assert_eq!(l.take_changes(), vec![Change::Splice { index: 2, removed: 2, inserted: 0 }]);
+		assert!(l.splice(5, 1, []).is_empty());
+		assert!(l.take_changes().is_empty());
+		assert_eq!(l.as_slice(), ["a", "b"]);
+	}
+
+	#[test]
+	fn edits_past_the_end() {
+		let mut l = list(&["a", "b"]);
+		assert_eq!(l.remove(2), None);
+		l.set_index(2, String::from("x"));
+		assert!(l.take_changes().is_empty());
+		l.insert(9, String::from("c"));
+		assert_eq!(l.take_changes(), vec![Change::Splice { index: 2, removed: 0, inserted: 1 }]);
+		assert_eq!(l.as_slice(), ["a", "b", "c"]);
+		assert_eq!(l.get_index(9), "");
+	}
+
+	#[test]
+	fn built_and_cloned_lists_start_inserted() {
+		let mut l: Iterable<String> = Iterable::from(["a", "b"]);
+		assert_eq!(l.take_changes(), vec![Change::Splice { index: 0, removed: 0, inserted: 2 }]);
+		assert_eq!(l.clone().take_changes(), vec![Change::Splice { index: 0, removed: 0, inserted: 2 }]);
+		assert!(Iterable::<String>::default().clone().take_changes().is_empty());
+	}
+
+	#[test]
+	fn changed_rows_follow_the_edits() {
+		let mut l = list(&["a", "b", "c", "d"]);
+		l.set_index(1, String::from("x"));
+		l.remove(0);
+		let changes = l.take_changes();
+		// unkeyed, the items after the removed one shift into other elements
+		assert_eq!(changed_rows(&changes, l.len(), false), vec![true, true, true]);
+		assert_eq!(changed_rows(&changes, l.len(), true), vec![true, false, false]);
+
+		l.push(String::from("e"));
+		l.set_index(0, String::from("y"));
+		let changes = l.take_changes();
+		assert_eq!(changed_rows(&changes, l.len(), false), vec![true, false, false, true]);
+
+		// a list without edits changed none of its rows, and a new one all of them
+		assert_eq!(changed_rows(&[], l.len(), false), vec![false; 4]);
+		let changes = Iterable::<String>::from(["a", "b"]).take_changes();
+		assert_eq!(changed_rows(&changes, 2, true), vec![true, true]);
+	}
+
+	#[derive(Default, Debug)]
+	struct TestData;
+
+	impl ElementData for TestData {}
+
+	fn text(content: &str) -> ElementImpl {
+		ElementImpl::Text(Text { content: content.to_string() })
+	}
+
+	// runs a keyed repeater over `keys` and returns the ids of the elements it ends up with
+	fn repeat_keyed(parent: &mut GenericElement<TestData>, keys: &[&str]) -> Vec<usize> {
+		for (i, key) in keys.iter().enumerate() {
+			parent.keyed_element_in(text(key), i, Key::from(*key));
+		}
+		parent.end_keyed_group(keys.len());
+		parent.children.iter().map(|e| e.id()).collect()
+	}
+
+	fn contents(parent: &GenericElement<TestData>) -> Vec<&str> {
+		parent.children.iter().map(|e| match &e.element_impl {
+			ElementImpl::Text(text) => text.content.as_str(),
+			_ => unreachable!(),
+		}).collect()
+	}
+
+	#[test]
+	fn keyed_elements_move_with_their_keys() {
+		let mut parent = GenericElement::<TestData>::root();
+		let ids = repeat_keyed(&mut parent, &["a", "b", "c"]);
+		let reordered = repeat_keyed(&mut parent, &["c", "a", "b"]);
+		assert_eq!(reordered, vec![ids[2], ids[0], ids[1]]);
+		assert_eq!(contents(&parent), vec!["c", "a", "b"]);
+	}
+
+	#[test]
+	fn new_keys_get_new_elements_in_place() {
+		let mut parent = GenericElement::<TestData>::root();
+		let ids = repeat_keyed(&mut parent, &["a", "c"]);
+		let inserted = repeat_keyed(&mut parent, &["a", "b", "c"]);
+		assert_eq!(inserted[0], ids[0]);
+		assert!(!ids.contains(&inserted[1]));
+		assert_eq!(inserted[2], ids[1]);
+		assert_eq!(contents(&parent), vec!["a", "b", "c"]);
+	}
+
+	#[test]
+	fn end_keyed_group_drops_the_removed_keys() {
+		let mut parent = GenericElement::<TestData>::root();
+		let ids = repeat_keyed(&mut parent, &["a", "b", "c", "d"]);
+		let remaining = repeat_keyed(&mut parent, &["d", "b"]);
+		assert_eq!(remaining, vec![ids[3], ids[1]]);
+		// the elements of removed keys are dropped, not hidden
+		assert_eq!(parent.children.len(), 2);
+		assert!(repeat_keyed(&mut parent, &[]).is_empty());
+	}
+
+	// renders `n` the way the compiler would for `text { (n) }`
+	#[derive(Debug, Default)]
+	struct Counter {
+		n: i32,
+		builds: usize,
+		dirty: Dirty,
+	}
+
+	impl Component for Counter {}
+
+	impl ComponentBase for Counter {
+		type Abi = NoAbi;
+
+		fn update<D: ElementData>(this: Rc<RefCell<Self>>, parent: &mut GenericElement<D>) {
+			let changes = this.borrow().dirty.take();
+			parent.element_in_with(0, changes & 1 != 0, || {
+				this.borrow_mut().builds += 1;
+				text(&this.borrow().n.to_string())
+			});
+		}
+
+		fn dirty(&self) -> &Dirty {
+			&self.dirty
+		}
+	}
+
+	#[test]
+	fn callbacks_mark_every_prop() {
+		let counter = Rc::new(RefCell::new(Counter::default()));
+		let callback: Callback<Counter> = Callback::from(&|c: &mut Counter| c.n += 1);
+		callback.bind(&counter).call();
+		assert_eq!(counter.borrow().n, 1);
+		assert_eq!(counter.borrow().dirty.take(), u64::MAX);
+	}
+
+	#[test]
+	fn unchanged_elements_are_restored_instead_of_built() {
+		let counter = Rc::new(RefCell::new(Counter::default()));
+		let mut root = GenericElement::<TestData>::root();
+		Counter::update(counter.clone(), &mut root);
+		assert_eq!((counter.borrow().builds, contents(&root)), (1, vec!["0"]));
+
+		// what layout wrote into the element is undone, but an unmarked write isn't picked up
+		root.children[0].element_impl = text("laid out");
+		counter.borrow_mut().n = 5;
+		Counter::update(counter.clone(), &mut root);
+		assert_eq!((counter.borrow().builds, contents(&root)), (1, vec!["0"]));
+
+		// nor is one marked with another prop's bit
+		counter.borrow().dirty.mark(2);
+		Counter::update(counter.clone(), &mut root);
+		assert_eq!((counter.borrow().builds, contents(&root)), (1, vec!["0"]));
+
+		counter.borrow().dirty.mark(1);
+		Counter::update(counter.clone(), &mut root);
+		assert_eq!((counter.borrow().builds, contents(&root)), (2, vec!["5"]));
+		assert_eq!(counter.borrow().dirty.take(), 0);
+	}
+
+	#[test]
+	fn only_shown_elements_are_restored() {
+		let mut parent = GenericElement::<TestData>::root();
+		assert!(!parent.restore_in(0));
+		parent.element_in(text("a"), 0).element_in(text("b"), 0);
+		parent.children[0].children[0].element_impl = text("laid out");
+		assert!(parent.restore_in(0));
+		assert_eq!(contents(&parent.children[0]), vec!["b"]);
+		parent.end_group(0);
+		assert!(!parent.restore_in(0));
+	}
+}
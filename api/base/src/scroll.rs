@@ -0,0 +1,94 @@
+use std::rc::Rc;
+use super::{Bounds, ElementData, ElementImpl, GenericElement, Length};
+
+// writes an offset the user scrolled to back into the component fields bound to it
+#[derive(Clone, Default)]
+pub struct ScrollBinding(Option<Rc<dyn Fn(Length, Length)>>);
+
+impl ScrollBinding {
+	pub fn new<F: Fn(Length, Length) + 'static>(f: F) -> Self {
+		ScrollBinding(Some(Rc::new(f)))
+	}
+	pub fn set(&self, x: Length, y: Length) {
+		if let Some(f) = self.0.as_ref() {
+			f(x, y);
+		}
+	}
+}
+
+impl std::fmt::Debug for ScrollBinding {
+	fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+		match self.0 {
+			Some(_) => write!(fmt, "ScrollBinding(..)"),
+			None => write!(fmt, "ScrollBinding(None)"),
+		}
+	}
+}
+
+// a viewport onto its children, which are moved up and left by the offset
+#[derive(Debug, Clone)]
+pub struct Scroll {
+	pub bounds: Bounds,
+	// `None` leaves the content wherever it was last scrolled to
+	pub offset_x: Option<Length>,
+	pub offset_y: Option<Length>,
+	pub binding: ScrollBinding,
+}
+
+impl Scroll {
+	pub fn offset(&self) -> (f32, f32) {
+		(
+			self.offset_x.as_ref().map(Length::to_px).unwrap_or(0.0),
+			self.offset_y.as_ref().map(Length::to_px).unwrap_or(0.0),
+		)
+	}
+
+	// called by the backends when the user scrolls
+	pub fn scroll_to(&mut self, x: f32, y: f32) {
+		self.offset_x = Some(Length::Px(x));
+		self.offset_y = Some(Length::Px(y));
+		self.binding.set(Length::Px(x), Length::Px(y));
+	}
+
+	// an offset the component leaves unset stays where it was scrolled to
+	pub(crate) fn keep_offset(&mut self, previous: &Scroll) {
+		if self.offset_x.is_none() {
+			self.offset_x = previous.offset_x.clone();
+		}
+		if self.offset_y.is_none() {
+			self.offset_y = previous.offset_y.clone();
+		}
+	}
+}
+
+// the right and bottom edge of everything inside an element, looking through groups
+fn content_size<D: ElementData>(children: &[GenericElement<D>]) -> (f32, f32) {
+	children.iter().filter(|e| e.show).fold((0.0, 0.0), |(width, height), e| {
+		let (w, h) = match e.element_impl.bounds() {
+			Some(b) => (b.x + b.width, b.y + b.height),
+			None => content_size(&e.children),
+		};
+		(f32::max(width, w), f32::max(height, h))
+	})
+}
+
+impl <D: ElementData> GenericElement<D> {
+	// moves the content of a `scroll` element by (dx, dy) without going past its edges;
+	// returns whether it moved
+	pub fn scroll_by(&mut self, dx: f32, dy: f32) -> bool {
+		let (content_width, content_height) = content_size(&self.children);
+		let scroll = match &mut self.element_impl {
+			ElementImpl::Scroll(scroll) => scroll,
+			_ => return false,
+		};
+		let bounds = scroll.bounds.to_px_bounds();
+		let (x, y) = scroll.offset();
+		let new_x = (x + dx).min(content_width - bounds.width).max(0.0);
+		let new_y = (y + dy).min(content_height - bounds.height).max(0.0);
+		if (new_x, new_y) == (x, y) {
+			return false;
+		}
+		scroll.scroll_to(new_x, new_y);
+		true
+	}
+}
@@ -0,0 +1,100 @@
+use super::{Bounds, Calc, ElementData, ElementImpl, GenericElement, Layout, Length, Rect};
+
+// what relative lengths are measured against, in logical pixels
+#[derive(Debug, Clone, Copy)]
+pub struct LengthContext {
+	// the content box of the parent, which `%` refers to
+	pub width: f32,
+	pub height: f32,
+	pub font_size: f32,
+	pub viewport_width: f32,
+	pub viewport_height: f32,
+}
+
+impl Length {
+	// `percent_of` is the parent's width for horizontal lengths and its height for vertical ones
+	pub fn resolve(&self, ctx: &LengthContext, percent_of: f32) -> f32 {
+		match *self {
+			Length::Percent(n) => n / 100.0 * percent_of,
+			Length::Em(n) => n * ctx.font_size,
+			Length::Vw(n) => n / 100.0 * ctx.viewport_width,
+			Length::Vh(n) => n / 100.0 * ctx.viewport_height,
+			Length::Calc(Calc { px, percent, em, vw, vh }) => {
+				px
+					+ Length::Percent(percent).resolve(ctx, percent_of)
+					+ Length::Em(em).resolve(ctx, percent_of)
+					+ Length::Vw(vw).resolve(ctx, percent_of)
+					+ Length::Vh(vh).resolve(ctx, percent_of)
+			},
+			_ => self.to_px(),
+		}
+	}
+}
+
+fn resolve(length: &mut Length, ctx: &LengthContext, percent_of: f32) {
+	if length.is_relative() {
+		*length = Length::Px(length.resolve(ctx, percent_of));
+	}
+}
+
+fn resolve_bounds(bounds: &mut Bounds, ctx: &LengthContext) {
+	resolve(&mut bounds.x, ctx, ctx.width);
+	resolve(&mut bounds.y, ctx, ctx.height);
+	resolve(&mut bounds.width, ctx, ctx.width);
+	resolve(&mut bounds.height, ctx, ctx.height);
+}
+
+fn resolve_rect(rect: &mut Rect, ctx: &LengthContext) {
+	resolve_bounds(&mut rect.bounds, ctx);
+	resolve(&mut rect.border.width, ctx, ctx.width);
+	resolve(&mut rect.radius, ctx, ctx.width);
+	resolve(&mut rect.shadow.x, ctx, ctx.width);
+	resolve(&mut rect.shadow.y, ctx, ctx.height);
+	resolve(&mut rect.shadow.blur, ctx, ctx.width);
+}
+
+// turns the relative lengths of an element and everything inside it into pixels; parents come
+// first so that children are measured against their resolved size. Backends that can't leave
+// relative lengths to the platform run this before `arrange`
+pub fn resolve_lengths<D: ElementData>(e: &mut GenericElement<D>, ctx: &LengthContext) {
+	match &mut e.element_impl {
+		ElementImpl::Rect(rect) => resolve_rect(rect, ctx),
+		ElementImpl::Layout(layout) => {
+			let Layout { rect, padding, spacing, .. } = layout;
+			resolve_rect(rect, ctx);
+			resolve(padding, ctx, ctx.width);
+			resolve(spacing, ctx, ctx.width);
+		},
+		ElementImpl::Scroll(scroll) => {
+			resolve_bounds(&mut scroll.bounds, ctx);
+			if let Some(x) = scroll.offset_x.as_mut() {
+				resolve(x, ctx, ctx.width);
+			}
+			if let Some(y) = scroll.offset_y.as_mut() {
+				resolve(y, ctx, ctx.height);
+			}
+		},
+		ElementImpl::Img(img) => resolve_bounds(&mut img.bounds, ctx),
+		ElementImpl::Span(span) => {
+			resolve(&mut span.x, ctx, ctx.width);
+			resolve(&mut span.y, ctx, ctx.height);
+		},
+		_ => {},
+	}
+
+	let border = match &e.element_impl {
+		ElementImpl::Rect(rect) | ElementImpl::Layout(Layout { rect, .. }) => rect.border_width(),
+		_ => 0.0,
+	};
+	let ctx = match e.element_impl.bounds() {
+		Some(bounds) => LengthContext {
+			width: (bounds.width - border * 2.0).max(0.0),
+			height: (bounds.height - border * 2.0).max(0.0),
+			..*ctx
+		},
+		None => *ctx,
+	};
+	for child in e.children.iter_mut() {
+		resolve_lengths(child, &ctx);
+	}
+}
@@ -1,11 +1,75 @@
-
-
 struct VertexOutput {
     [[builtin(position)]] clip_position: vec4<f32>;
-    [[location(0)]] color: vec4<f32>;
+    // the position relative to the center of the shape, in logical pixels
+    [[location(0)]] local: vec2<f32>;
+    [[location(1)]] half_size: vec2<f32>;
+    // corner radius, border width, the distance over which the edge fades out and whether
+    // the shape is filled with the gradient instead of `color`
+    [[location(2)]] shape: vec4<f32>;
+    // premultiplied
+    [[location(3)]] color: vec4<f32>;
+    [[location(4)]] border_color: vec4<f32>;
+};
+
+// up to 8 stops with premultiplied colors; `kind` is 1 for a linear gradient, 2 for a radial one
+struct Gradient {
+    kind: u32;
+    count: u32;
+    angle: f32;
+    stops: array<vec4<f32>, 8>;
+    positions: array<vec4<f32>, 2>;
 };
 
+[[group(0), binding(0)]]
+var<uniform> gradient: Gradient;
+
+// the signed distance to the edge of a rounded box, negative inside
+fn rounded_box(p: vec2<f32>, half_size: vec2<f32>, radius: f32) -> f32 {
+    let q = abs(p) - half_size + vec2<f32>(radius);
+    return length(max(q, vec2<f32>(0.0))) + min(max(q.x, q.y), 0.0) - radius;
+}
+
+fn stop_position(i: u32) -> f32 {
+    return gradient.positions[i / 4u][i % 4u];
+}
+
+// the position along the gradient line, as in css
+fn gradient_position(p: vec2<f32>, half_size: vec2<f32>) -> f32 {
+    if(gradient.kind == 1u) {
+        let direction = vec2<f32>(sin(gradient.angle), -cos(gradient.angle));
+        let extent = 2.0 * (abs(half_size.x * direction.x) + abs(half_size.y * direction.y));
+        return dot(p, direction) / max(extent, 0.0001) + 0.5;
+    }
+    return length(p / max(half_size, vec2<f32>(0.0001))) / sqrt(2.0);
+}
+
+fn fill(in: VertexOutput) -> vec4<f32> {
+    if(in.shape.w == 0.0 || gradient.kind == 0u) {
+        return in.color;
+    }
+    let t = gradient_position(in.local, in.half_size);
+    var color = gradient.stops[0];
+    for(var i: u32 = 1u; i < gradient.count; i = i + 1u) {
+        let from = stop_position(i - 1u);
+        let to = stop_position(i);
+        color = mix(color, gradient.stops[i], clamp((t - from) / max(to - from, 0.0001), 0.0, 1.0));
+    }
+    return color;
+}
+
 [[stage(fragment)]]
 fn fs_main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
-    return vec4<f32>(in.color);
-}
\ No newline at end of file
+    let radius = min(in.shape.x, min(in.half_size.x, in.half_size.y));
+    let border = in.shape.y;
+    let softness = in.shape.z;
+
+    let outer = rounded_box(in.local, in.half_size, radius);
+    let coverage = clamp(0.5 - outer / softness, 0.0, 1.0);
+
+    var color = fill(in);
+    if(border > 0.0) {
+        let inner = rounded_box(in.local, in.half_size - vec2<f32>(border), max(radius - border, 0.0));
+        color = mix(color, in.border_color, clamp(0.5 + inner / softness, 0.0, 1.0));
+    }
+    return color * coverage;
+}
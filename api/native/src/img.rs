@@ -0,0 +1,271 @@
+use std::collections::HashMap;
+use std::num::NonZeroU32;
+use wgpu::util::DeviceExt;
+use bytemuck::{Zeroable, Pod};
+
+use super::{ElementContext, Fit, Img, PxBounds, RenderContext, scissor_rect};
+
+struct Texture {
+	bind_group: wgpu::BindGroup,
+	width: u32,
+	height: u32,
+}
+
+struct Pipeline {
+	pipeline: wgpu::RenderPipeline,
+	bind_group_layout: wgpu::BindGroupLayout,
+	sampler: wgpu::Sampler,
+}
+
+// decoded images stay on the GPU for as long as the window is open, keyed by their `src`;
+// images that failed to load are remembered as well so that they aren't retried every frame
+#[derive(Default)]
+pub struct ImageCache {
+	textures: HashMap<String, Option<Texture>>,
+	pipeline: Option<Pipeline>,
+}
+
+impl ImageCache {
+	fn pipeline(&mut self, device: &wgpu::Device, format: wgpu::TextureFormat) -> &Pipeline {
+		self.pipeline.get_or_insert_with(|| {
+			let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+				label: None,
+				entries: &[
+					wgpu::BindGroupLayoutEntry {
+						binding: 0,
+						visibility: wgpu::ShaderStages::FRAGMENT,
+						ty: wgpu::BindingType::Texture {
+							sample_type: wgpu::TextureSampleType::Float { filterable: true },
+							view_dimension: wgpu::TextureViewDimension::D2,
+							multisampled: false,
+						},
+						count: None,
+					},
+					wgpu::BindGroupLayoutEntry {
+						binding: 1,
+						visibility: wgpu::ShaderStages::FRAGMENT,
+						ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+						count: None,
+					},
+				],
+			});
+			let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+				label: None,
+				bind_group_layouts: &[&bind_group_layout],
+				push_constant_ranges: &[],
+			});
+			let shader = device.create_shader_module(&wgpu::include_wgsl!("img.wgsl"));
+			let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+				label: None,
+				layout: Some(&layout),
+				primitive: wgpu::PrimitiveState {
+					topology: wgpu::PrimitiveTopology::TriangleList,
+					..Default::default()
+				},
+				vertex: wgpu::VertexState {
+					module: &shader,
+					entry_point: "vs_main",
+					buffers: &[Vertex::layout()],
+				},
+				fragment: Some(wgpu::FragmentState {
+					module: &shader,
+					entry_point: "fs_main",
+					targets: &[wgpu::ColorTargetState {
+						format,
+						blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+						write_mask: wgpu::ColorWrites::ALL,
+					}],
+				}),
+				depth_stencil: None,
+				multisample: Default::default(),
+				multiview: None,
+			});
+			let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+				mag_filter: wgpu::FilterMode::Linear,
+				min_filter: wgpu::FilterMode::Linear,
+				..Default::default()
+			});
+			Pipeline { pipeline, bind_group_layout, sampler }
+		})
+	}
+
+	fn texture(&mut self, src: &str, device: &wgpu::Device, queue: &wgpu::Queue, format: wgpu::TextureFormat) -> Option<&Texture> {
+		if !self.textures.contains_key(src) {
+			let texture = match image::open(src) {
+				Ok(image) => {
+					let pipeline = self.pipeline(device, format);
+					Some(upload(&image.to_rgba8(), pipeline, device, queue))
+				},
+				Err(err) => {
+					eprintln!("failed to load image `{src}`: {err}");
+					None
+				},
+			};
+			self.textures.insert(src.to_owned(), texture);
+		}
+		self.textures[src].as_ref()
+	}
+}
+
+fn upload(image: &image::RgbaImage, pipeline: &Pipeline, device: &wgpu::Device, queue: &wgpu::Queue) -> Texture {
+	let (width, height) = image.dimensions();
+	let size = wgpu::Extent3d { width, height, depth_or_array_layers: 1 };
+	let texture = device.create_texture(&wgpu::TextureDescriptor {
+		label: None,
+		size,
+		mip_level_count: 1,
+		sample_count: 1,
+		dimension: wgpu::TextureDimension::D2,
+		format: wgpu::TextureFormat::Rgba8UnormSrgb,
+		usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+	});
+	queue.write_texture(
+		wgpu::ImageCopyTexture {
+			texture: &texture,
+			mip_level: 0,
+			origin: wgpu::Origin3d::ZERO,
+			aspect: wgpu::TextureAspect::All,
+		},
+		image,
+		wgpu::ImageDataLayout {
+			offset: 0,
+			bytes_per_row: NonZeroU32::new(4 * width),
+			rows_per_image: NonZeroU32::new(height),
+		},
+		size,
+	);
+	let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+	let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+		label: None,
+		layout: &pipeline.bind_group_layout,
+		entries: &[
+			wgpu::BindGroupEntry {
+				binding: 0,
+				resource: wgpu::BindingResource::TextureView(&view),
+			},
+			wgpu::BindGroupEntry {
+				binding: 1,
+				resource: wgpu::BindingResource::Sampler(&pipeline.sampler),
+			},
+		],
+	});
+	Texture { bind_group, width, height }
+}
+
+// where the image is drawn and which part of it (in texture coordinates) ends up there
+fn fit_image(fit: Fit, bounds: &PxBounds, width: f32, height: f32) -> (PxBounds, (f32, f32, f32, f32)) {
+	match fit {
+		Fit::Fill => (bounds.clone(), (0.0, 0.0, 1.0, 1.0)),
+		Fit::Contain => {
+			let scale = f32::min(bounds.width / width, bounds.height / height);
+			let (w, h) = (width * scale, height * scale);
+			let dest = PxBounds {
+				x: bounds.x + (bounds.width - w) / 2.0,
+				y: bounds.y + (bounds.height - h) / 2.0,
+				width: w,
+				height: h,
+			};
+			(dest, (0.0, 0.0, 1.0, 1.0))
+		},
+		Fit::Cover => {
+			let scale = f32::max(bounds.width / width, bounds.height / height);
+			let u = bounds.width / (width * scale);
+			let v = bounds.height / (height * scale);
+			(bounds.clone(), ((1.0 - u) / 2.0, (1.0 - v) / 2.0, (1.0 + u) / 2.0, (1.0 + v) / 2.0))
+		},
+	}
+}
+
+pub fn render_img(img: &Img, ectx: &ElementContext, rctx: &mut RenderContext) {
+	let (clip_x, clip_y, clip_width, clip_height) = match scissor_rect(ectx, rctx.surface_config) {
+		Some(rect) => rect,
+		None => return,
+	};
+	let format = rctx.surface_config.format;
+	let (width, height) = match rctx.images.texture(&img.src, rctx.device, rctx.queue, format) {
+		Some(texture) => (texture.width as f32, texture.height as f32),
+		None => return,
+	};
+	let (dest, (u1, v1, u2, v2)) = fit_image(img.fit, &ectx.bounds, width, height);
+
+	let vw = ectx.vw / ectx.scale_factor / 2.0;
+	let vh = ectx.vh / ectx.scale_factor / 2.0;
+	let x1 = dest.x / vw - 1.0;
+	let x2 = x1 + dest.width / vw;
+	let y1 = -dest.y / vh + 1.0;
+	let y2 = y1 - dest.height / vh;
+	let opacity = ectx.opacity;
+	let vertices = [
+		Vertex { x: x1, y: y1, u: u1, v: v1, opacity },
+		Vertex { x: x1, y: y2, u: u1, v: v2, opacity },
+		Vertex { x: x2, y: y1, u: u2, v: v1, opacity },
+		Vertex { x: x2, y: y2, u: u2, v: v2, opacity },
+	];
+	let indices: [u16; 6] = [0, 1, 2, 1, 2, 3];
+	let vertex_buf = rctx.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+		label: None,
+		contents: bytemuck::cast_slice(&vertices),
+		usage: wgpu::BufferUsages::VERTEX,
+	});
+	let index_buf = rctx.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+		label: None,
+		contents: bytemuck::cast_slice(&indices),
+		usage: wgpu::BufferUsages::INDEX,
+	});
+
+	let view = rctx.frame.texture.create_view(&wgpu::TextureViewDescriptor::default());
+	let texture = rctx.images.textures[&img.src].as_ref().unwrap();
+	let pipeline = rctx.images.pipeline.as_ref().unwrap();
+	let mut render_pass = rctx.encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+		label: None,
+		color_attachments: &[wgpu::RenderPassColorAttachment {
+			view: &view,
+			resolve_target: None,
+			ops: wgpu::Operations {
+				load: wgpu::LoadOp::Load,
+				store: true,
+			},
+		}],
+		depth_stencil_attachment: None,
+	});
+	render_pass.set_pipeline(&pipeline.pipeline);
+	render_pass.set_scissor_rect(clip_x, clip_y, clip_width, clip_height);
+	render_pass.set_bind_group(0, &texture.bind_group, &[]);
+	render_pass.set_vertex_buffer(0, vertex_buf.slice(..));
+	render_pass.set_index_buffer(index_buf.slice(..), wgpu::IndexFormat::Uint16);
+	render_pass.draw_indexed(0..6, 0, 0..1);
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+struct Vertex {
+	x: f32, y: f32,
+	u: f32, v: f32,
+	opacity: f32,
+}
+
+impl Vertex {
+	fn layout<'a>() -> wgpu::VertexBufferLayout<'a> {
+		wgpu::VertexBufferLayout {
+			array_stride: std::mem::size_of::<Vertex>() as wgpu::BufferAddress,
+			step_mode: wgpu::VertexStepMode::Vertex,
+			attributes: &[
+				wgpu::VertexAttribute {
+					offset: 0,
+					shader_location: 0,
+					format: wgpu::VertexFormat::Float32x2,
+				},
+				wgpu::VertexAttribute {
+					offset: (std::mem::size_of::<f32>() * 2) as wgpu::BufferAddress,
+					shader_location: 1,
+					format: wgpu::VertexFormat::Float32x2,
+				},
+				wgpu::VertexAttribute {
+					offset: (std::mem::size_of::<f32>() * 4) as wgpu::BufferAddress,
+					shader_location: 2,
+					format: wgpu::VertexFormat::Float32,
+				},
+			]
+		}
+	}
+}
@@ -0,0 +1,33 @@
+struct VertexInput {
+    [[location(0)]] position: vec2<f32>;
+    [[location(1)]] uv: vec2<f32>;
+    [[location(2)]] opacity: f32;
+};
+
+struct VertexOutput {
+    [[builtin(position)]] clip_position: vec4<f32>;
+    [[location(0)]] uv: vec2<f32>;
+    [[location(1)]] opacity: f32;
+};
+
+[[group(0), binding(0)]]
+var image: texture_2d<f32>;
+[[group(0), binding(1)]]
+var image_sampler: sampler;
+
+[[stage(vertex)]]
+fn vs_main(
+    model: VertexInput,
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.uv = model.uv;
+    out.opacity = model.opacity;
+    out.clip_position = vec4<f32>(model.position, 0.0, 1.0);
+    return out;
+}
+
+[[stage(fragment)]]
+fn fs_main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    let color = textureSample(image, image_sampler, in.uv);
+    return vec4<f32>(color.rgb, color.a * in.opacity);
+}
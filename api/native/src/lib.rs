@@ -1,18 +1,24 @@
 use std::rc::Rc;
 use std::cell::RefCell;
 use winit::{
+	dpi::LogicalSize,
 	event::{Event, WindowEvent},
 	event_loop::{ControlFlow, EventLoop},
-	window::{Window},
+	window::WindowBuilder,
 };
 use wgpu::util::DeviceExt;
 use wgpu_text::{
 	BrushBuilder,
+	ScissorRegion,
 	section::OwnedSection
 };
 
 pub use ui_base::*;
 
+mod img;
+
+use img::ImageCache;
+
 #[derive(Default, Debug)]
 pub struct NativeElementData;
 impl ElementData for NativeElementData {}
@@ -22,8 +28,13 @@ pub type Abi = NoAbi;
 
 const TIMES_NEW_ROMAN: &[u8] = include_bytes!("./Times New Roman.ttf");
 
+// the size of all text, which is also what `em` refers to
+const FONT_SIZE: f32 = 35.0;
+
 pub struct ComponentWindow<C: ComponentBase + 'static> {
-	window: Window,
+	window: winit::window::Window,
+	// the `window` root element as last applied to `window`
+	settings: Option<Window>,
 	background: wgpu::Color,
 	surface: wgpu::Surface,
 	event_loop: EventLoop<()>,
@@ -31,6 +42,14 @@ pub struct ComponentWindow<C: ComponentBase + 'static> {
 	component: Rc<RefCell<C>>,
 	root: Element,
 	pointer_position: (f32, f32),
+	drag: Option<Drag>,
+	images: ImageCache,
+}
+
+// a press inside one or more `scroll` elements, which follow the pointer until it is released
+struct Drag {
+	scrolls: Vec<usize>,
+	moved: bool,
 }
 
 pub struct FontStyle {
@@ -47,6 +66,7 @@ pub struct RenderContext<'a> {
 	pub queue: &'a wgpu::Queue,
 	pub text_sections: Vec<OwnedSection>,
 	pub font_styles: Vec<FontStyle>,
+	pub images: &'a mut ImageCache,
 }
 
 pub trait RenderNative {
@@ -57,16 +77,20 @@ pub trait RenderNative {
 impl RenderNative for ElementImpl {
 	fn render<'a>(&self, ectx: &ElementContext, rctx: &mut RenderContext) {
 		match self {
-			ElementImpl::Root(..)|ElementImpl::Group => {},
+			ElementImpl::Root(..)|ElementImpl::Group|ElementImpl::Window(_)|ElementImpl::Scroll(_) => {},
 			ElementImpl::Rect(rect) => RenderNative::render(rect, ectx, rctx),
+			ElementImpl::Layout(layout) => RenderNative::render(&layout.rect, ectx, rctx),
+			ElementImpl::Img(img) => img::render_img(img, ectx, rctx),
 			ElementImpl::Span(span) => RenderNative::render(span, ectx, rctx),
 			ElementImpl::Text(text) => RenderNative::render(text, ectx, rctx),
 		}
 	}
 	fn font_style(&self) -> Option<FontStyle> {
 		match self {
-			ElementImpl::Root(..)|ElementImpl::Group => None,
+			ElementImpl::Root(..)|ElementImpl::Group|ElementImpl::Window(_)|ElementImpl::Scroll(_) => None,
 			ElementImpl::Rect(rect) => rect.font_style(),
+			ElementImpl::Layout(layout) => layout.rect.font_style(),
+			ElementImpl::Img(_) => None,
 			ElementImpl::Span(span) => span.font_style(),
 			ElementImpl::Text(text) => text.font_style(),
 		}
@@ -76,14 +100,14 @@ impl RenderNative for ElementImpl {
 impl RenderNative for Span {
 	fn font_style(&self) -> Option<FontStyle> {
 		Some(FontStyle {
-			size: 35.0,
-			color: self.color.clone(),
+			size: FONT_SIZE,
+			color: self.color.fallback_color(),
 		})
 	}
 }
 
 impl RenderNative for Text {
-	fn render(&self, _ectx: &ElementContext, rctx: &mut RenderContext) {
+	fn render(&self, ectx: &ElementContext, rctx: &mut RenderContext) {
 		let style = rctx.font_styles.last().unwrap();
 		rctx.text_sections.last_mut().unwrap().text.push(
 			wgpu_text::section::OwnedText::new(&self.content)
@@ -92,7 +116,7 @@ impl RenderNative for Text {
 					f32::powf(style.color.r as f32 / 255.0, 2.2),
 					f32::powf(style.color.g as f32 / 255.0, 2.2),
 					f32::powf(style.color.b as f32 / 255.0, 2.2),
-					style.color.a as f32,
+					style.color.a * ectx.opacity,
 				]),
 		);
 	}
@@ -100,6 +124,11 @@ impl RenderNative for Text {
 
 impl RenderNative for Rect {
 	fn render(&self, ectx: &ElementContext, rctx: &mut RenderContext) {
+		let (clip_x, clip_y, clip_width, clip_height) = match scissor_rect(ectx, rctx.surface_config) {
+			Some(rect) => rect,
+			None => return,
+		};
+
 		let view = rctx.frame.texture.create_view(&wgpu::TextureViewDescriptor::default());
 		
 		let pipeline = rctx.device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
@@ -119,7 +148,7 @@ impl RenderNative for Rect {
 				entry_point: "fs_main",
 				targets: &[wgpu::ColorTargetState {
 					format: rctx.surface_config.format,
-					blend: Some(wgpu::BlendState::REPLACE),
+					blend: Some(wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING),
 					write_mask: wgpu::ColorWrites::ALL,
 				}],
 			}),
@@ -139,6 +168,19 @@ impl RenderNative for Rect {
 			contents: bytemuck::cast_slice(&indices),
 			usage: wgpu::BufferUsages::INDEX,
 		});
+		let gradient_buf = rctx.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+			label: None,
+			contents: bytemuck::bytes_of(&GradientUniform::new(&self.color, ectx.opacity)),
+			usage: wgpu::BufferUsages::UNIFORM,
+		});
+		let bind_group = rctx.device.create_bind_group(&wgpu::BindGroupDescriptor {
+			label: None,
+			layout: &pipeline.get_bind_group_layout(0),
+			entries: &[wgpu::BindGroupEntry {
+				binding: 0,
+				resource: gradient_buf.as_entire_binding(),
+			}],
+		});
 		
 		{
 			let mut render_pass = rctx.encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
@@ -154,30 +196,148 @@ impl RenderNative for Rect {
 				depth_stencil_attachment: None,
 			});
 			render_pass.set_pipeline(&pipeline);
+			render_pass.set_bind_group(0, &bind_group, &[]);
+			render_pass.set_scissor_rect(clip_x, clip_y, clip_width, clip_height);
 			render_pass.set_vertex_buffer(0, vertex_buf.slice(..));
 			render_pass.set_index_buffer(index_buf.slice(..), wgpu::IndexFormat::Uint16);
-			render_pass.draw_indexed(0..6, 0, 0..1);
+			render_pass.draw_indexed(0..indices.len() as u32, 0, 0..1);
 		}
 	}
 }
 
+// the part of the surface an element may draw to, in physical pixels; `None` if there is none
+pub(crate) fn scissor_rect(ctx: &ElementContext, config: &wgpu::SurfaceConfiguration) -> Option<(u32, u32, u32, u32)> {
+	let clip = ctx.clip.to_raw(ctx.scale_factor);
+	let x1 = clip.x.max(0.0).min(config.width as f32);
+	let y1 = clip.y.max(0.0).min(config.height as f32);
+	let x2 = (clip.x + clip.width).max(0.0).min(config.width as f32);
+	let y2 = (clip.y + clip.height).max(0.0).min(config.height as f32);
+	let (x1, y1, x2, y2) = (x1.floor() as u32, y1.floor() as u32, x2.ceil() as u32, y2.ceil() as u32);
+	if x2 <= x1 || y2 <= y1 {
+		None
+	} else {
+		Some((x1, y1, x2 - x1, y2 - y1))
+	}
+}
+
+// the shadow, then the rect itself; borders and rounded corners are left to the fragment shader
 fn build_rect_vertices(rect: &Rect, ctx: &ElementContext) -> (Vec<Vertex>, Vec<u16>) {
+	// edges are smoothed over one physical pixel
+	let pixel = 1.0 / ctx.scale_factor;
+	let radius = rect.radius.to_px().max(0.0);
+	let mut quads = Vec::new();
+	let shadow = &rect.shadow;
+	let shadow_color = shadow.color.fallback_color();
+	if shadow_color.a > 0.0 {
+		let mut bounds = ctx.bounds.clone();
+		bounds.x += shadow.x.to_px();
+		bounds.y += shadow.y.to_px();
+		let shape = Shape {
+			radius,
+			border: 0.0,
+			softness: shadow.blur.to_px().max(pixel),
+			gradient: false,
+		};
+		quads.push((bounds, shape, shadow_color.clone(), shadow_color));
+	}
+	let shape = Shape {
+		radius,
+		border: rect.border_width(),
+		softness: pixel,
+		gradient: !matches!(rect.color, Brush::Color(_)),
+	};
+	quads.push((ctx.bounds.clone(), shape, rect.color.fallback_color(), rect.border.color.fallback_color()));
+
+	let mut vertices = Vec::new();
+	let mut indices = Vec::new();
+	for (bounds, shape, color, border_color) in quads {
+		let first = vertices.len() as u16;
+		vertices.extend(quad_vertices(&bounds, &shape, &color, &border_color, ctx));
+		indices.extend([0, 1, 2, 1, 2, 3].map(|i| first + i));
+	}
+	(vertices, indices)
+}
+
+struct Shape {
+	radius: f32,
+	border: f32,
+	// how far the edge fades out: a pixel, or the blur of a shadow
+	softness: f32,
+	// filled with the gradient of the rect's brush rather than a color
+	gradient: bool,
+}
+
+// a quad around `bounds` that leaves room for the edge to fade out
+fn quad_vertices(bounds: &PxBounds, shape: &Shape, color: &Color, border_color: &Color, ctx: &ElementContext) -> Vec<Vertex> {
 	let vw = ctx.vw / ctx.scale_factor / 2.0;
 	let vh = ctx.vh / ctx.scale_factor / 2.0;
-	let x1 = ctx.bounds.x / vw - 1.0;
-	let x2 = x1 + ctx.bounds.width / vw;
-	let y1 = -ctx.bounds.y / vh + 1.0;
-	let y2 = y1 - ctx.bounds.height / vh;
-	let vertices = vertices(
-		f32::powf(rect.color.r as f32 / 255.0, 2.2),
-		f32::powf(rect.color.g as f32 / 255.0, 2.2),
-		f32::powf(rect.color.b as f32 / 255.0, 2.2),
-		rect.color.a, &[
-		(x1, y1),(x1, y2),
-		(x2, y1),(x2, y2),
-	]);
-	let indices = vec![0, 1, 2, 1, 2, 3];
-	(vertices, indices)
+	let half_width = bounds.width / 2.0;
+	let half_height = bounds.height / 2.0;
+	let center_x = bounds.x + half_width;
+	let center_y = bounds.y + half_height;
+	let color = premultiplied(color, ctx.opacity);
+	let border_color = premultiplied(border_color, ctx.opacity);
+	[(-1.0, -1.0), (-1.0, 1.0), (1.0, -1.0), (1.0, 1.0)].into_iter().map(|(dx, dy): (f32, f32)| {
+		let local_x = dx * (half_width + shape.softness);
+		let local_y = dy * (half_height + shape.softness);
+		Vertex {
+			x: (center_x + local_x) / vw - 1.0,
+			y: 1.0 - (center_y + local_y) / vh,
+			local_x,
+			local_y,
+			half_width,
+			half_height,
+			radius: shape.radius,
+			border: shape.border,
+			softness: shape.softness,
+			gradient: if shape.gradient { 1.0 } else { 0.0 },
+			color,
+			border_color,
+		}
+	})
+	.collect()
+}
+
+// at most this many stops of a gradient are drawn
+const MAX_GRADIENT_STOPS: usize = 8;
+
+// the `Gradient` uniform of fragment.wgsl
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+struct GradientUniform {
+	kind: u32,
+	count: u32,
+	angle: f32,
+	_padding: f32,
+	stops: [[f32; 4]; MAX_GRADIENT_STOPS],
+	positions: [f32; MAX_GRADIENT_STOPS],
+}
+
+impl GradientUniform {
+	fn new(brush: &Brush, opacity: f32) -> Self {
+		let (kind, angle) = match brush {
+			Brush::Color(_) => (0, 0.0),
+			Brush::Linear { angle, .. } => (1, angle.to_radians()),
+			Brush::Radial { .. } => (2, 0.0),
+		};
+		let mut uniform = GradientUniform { kind, angle, ..Zeroable::zeroed() };
+		for (i, (position, color)) in brush.resolved_stops().iter().take(MAX_GRADIENT_STOPS).enumerate() {
+			uniform.stops[i] = premultiplied(color, opacity);
+			uniform.positions[i] = *position;
+			uniform.count += 1;
+		}
+		uniform
+	}
+}
+
+fn premultiplied(color: &Color, opacity: f32) -> [f32; 4] {
+	let a = color.a * opacity;
+	[
+		f32::powf(color.r as f32 / 255.0, 2.2) * a,
+		f32::powf(color.g as f32 / 255.0, 2.2) * a,
+		f32::powf(color.b as f32 / 255.0, 2.2) * a,
+		a,
+	]
 }
 
 impl RenderNative for Element {
@@ -186,6 +346,28 @@ impl RenderNative for Element {
 
 		self.element_impl.render(&ctx, rctx);
 
+		// the content of a `scroll` is moved by its offset and cut off at its edges
+		let ctx = match &self.element_impl {
+			ElementImpl::Scroll(scroll) => {
+				let (x, y) = scroll.offset();
+				let mut bounds = ctx.bounds.clone();
+				let clip = intersect(&ctx.clip, &ctx.bounds);
+				bounds.x -= x;
+				bounds.y -= y;
+				ElementContext { bounds, clip, ..ctx }
+			},
+			ElementImpl::Rect(rect) | ElementImpl::Layout(Layout { rect, .. }) => {
+				let bounds = inset(&ctx.bounds, rect.border_width());
+				let clip = if rect.clip {
+					intersect(&ctx.clip, &bounds)
+				} else {
+					ctx.clip.clone()
+				};
+				ElementContext { bounds, clip, ..ctx }
+			},
+			_ => ctx,
+		};
+
 		let pushed_font_style = if let Some(style) = self.element_impl.font_style() {
 			rctx.font_styles.push(style);
 			true
@@ -216,8 +398,15 @@ impl RenderNative for Element {
 			brush.queue(&section);
 
 			let view = rctx.frame.texture.create_view(&wgpu::TextureViewDescriptor::default());
-			let buf = brush.draw(rctx.device, &view, rctx.queue);
-			rctx.bufs.push(buf);
+			let region = scissor_rect(&ctx, rctx.surface_config).map(|(x, y, width, height)| ScissorRegion {
+				x, y, width, height,
+				out_width: rctx.surface_config.width,
+				out_height: rctx.surface_config.height,
+			});
+			if let Some(region) = region {
+				let buf = brush.draw_custom(rctx.device, &view, rctx.queue, Some(region));
+				rctx.bufs.push(buf);
+			}
 		}
 
 		if pushed_font_style {
@@ -230,25 +419,191 @@ fn bounds_contain_point(b: &PxBounds, point: &(f32, f32)) -> bool {
 	b.x <= point.0 && b.y <= point.1 && b.x+b.width >= point.0 && b.y+b.height >= point.1
 }
 
-fn find_element_at_px_point(e: &Element, point: (f32, f32)) -> &Element {
-	let bounds = e.element_impl.bounds().unwrap();
-	assert!(bounds_contain_point(&bounds, &point));
+fn intersect(a: &PxBounds, b: &PxBounds) -> PxBounds {
+	let x = a.x.max(b.x);
+	let y = a.y.max(b.y);
+	PxBounds {
+		x,
+		y,
+		width: ((a.x + a.width).min(b.x + b.width) - x).max(0.0),
+		height: ((a.y + a.height).min(b.y + b.height) - y).max(0.0),
+	}
+}
+
+fn inset(b: &PxBounds, d: f32) -> PxBounds {
+	PxBounds {
+		x: b.x + d,
+		y: b.y + d,
+		width: (b.width - d * 2.0).max(0.0),
+		height: (b.height - d * 2.0).max(0.0),
+	}
+}
+
+// where a point inside an element lands in the coordinates of its children
+fn point_in_children(e: &Element, bounds: &PxBounds, point: (f32, f32)) -> (f32, f32) {
+	let (x, y) = match &e.element_impl {
+		ElementImpl::Scroll(scroll) => scroll.offset(),
+		ElementImpl::Rect(rect) | ElementImpl::Layout(Layout { rect, .. }) => {
+			let border = rect.border_width();
+			(-border, -border)
+		},
+		_ => (0.0, 0.0),
+	};
+	(point.0 - bounds.x + x, point.1 - bounds.y + y)
+}
 
-	let point = (point.0 - bounds.x, point.1 - bounds.y);
-	for c in &e.children {
-		if let Some(bounds) = c.element_impl.bounds() {
-			if bounds_contain_point(&bounds, &point) {
-				return find_element_at_px_point(c, point);
+// elements without bounds of their own (groups, the window) are looked through
+fn find_element_at_px_point(e: &Element, point: (f32, f32)) -> Option<&Element> {
+	let bounds = e.element_impl.bounds();
+	let point = match &bounds {
+		Some(bounds) => {
+			if !bounds_contain_point(bounds, &point) {
+				return None;
+			}
+			point_in_children(e, bounds, point)
+		},
+		None => point,
+	};
+	e.children.iter()
+		.filter(|c| c.show)
+		.find_map(|c| find_element_at_px_point(c, point))
+		.or(bounds.map(|_| e))
+}
+
+// the ids of the `scroll` elements under a point, outermost first
+fn find_scrolls_at_px_point(e: &Element, point: (f32, f32), scrolls: &mut Vec<usize>) {
+	let point = match e.element_impl.bounds() {
+		Some(bounds) => {
+			if !bounds_contain_point(&bounds, &point) {
+				return;
+			}
+			if let ElementImpl::Scroll(_) = e.element_impl {
+				scrolls.push(e.id());
+			}
+			point_in_children(e, &bounds, point)
+		},
+		None => point,
+	};
+	for c in e.children.iter().filter(|c| c.show) {
+		find_scrolls_at_px_point(c, point, scrolls);
+	}
+}
+
+fn find_element_by_id(e: &mut Element, id: usize) -> Option<&mut Element> {
+	if e.id() == id {
+		return Some(e);
+	}
+	e.children.iter_mut().find_map(|c| find_element_by_id(c, id))
+}
+
+// scrolls the innermost of `scrolls` that can still move in that direction
+fn scroll_by(root: &mut Element, scrolls: &[usize], dx: f32, dy: f32) -> bool {
+	for &id in scrolls.iter().rev() {
+		if let Some(e) = find_element_by_id(root, id) {
+			if e.scroll_by(dx, dy) {
+				if let Some(callback) = &e.events.scroll {
+					callback.call();
+				}
+				return true;
 			}
 		}
 	}
+	false
+}
+
+fn window_settings(root: &Element) -> Option<&Window> {
+	match root.children.first().map(|e| &e.element_impl) {
+		Some(ElementImpl::Window(settings)) => Some(settings),
+		_ => None,
+	}
+}
+
+fn inner_size(settings: &Window) -> LogicalSize<f32> {
+	LogicalSize::new(settings.width.to_px(), settings.height.to_px())
+}
+
+// a limit set in one direction only leaves the other one open
+fn min_inner_size(settings: &Window) -> Option<LogicalSize<f32>> {
+	if settings.min_width.is_none() && settings.min_height.is_none() {
+		return None;
+	}
+	Some(LogicalSize::new(
+		settings.min_width.as_ref().map(Length::to_px).unwrap_or(0.0),
+		settings.min_height.as_ref().map(Length::to_px).unwrap_or(0.0),
+	))
+}
+
+fn max_inner_size(settings: &Window) -> Option<LogicalSize<f32>> {
+	if settings.max_width.is_none() && settings.max_height.is_none() {
+		return None;
+	}
+	let unlimited = f32::from(u16::MAX);
+	Some(LogicalSize::new(
+		settings.max_width.as_ref().map(Length::to_px).unwrap_or(unlimited),
+		settings.max_height.as_ref().map(Length::to_px).unwrap_or(unlimited),
+	))
+}
 
-	e
+fn configure_window(window_builder: WindowBuilder, settings: &Window) -> WindowBuilder {
+	let mut window_builder = window_builder
+		.with_title(&settings.title)
+		.with_inner_size(inner_size(settings))
+		.with_resizable(settings.resizable);
+	if let Some(size) = min_inner_size(settings) {
+		window_builder = window_builder.with_min_inner_size(size);
+	}
+	if let Some(size) = max_inner_size(settings) {
+		window_builder = window_builder.with_max_inner_size(size);
+	}
+	window_builder
+}
+
+// brings the open window in line with a `window` root whose bound properties have changed
+fn update_window(window: &winit::window::Window, applied: &mut Option<Window>, root: &Element) {
+	let settings = match window_settings(root) {
+		Some(settings) => settings,
+		None => return,
+	};
+	if applied.as_ref() == Some(settings) {
+		return;
+	}
+	let changed = |differs: &dyn Fn(&Window) -> bool| applied.as_ref().map(differs).unwrap_or(true);
+	if changed(&|a| a.title != settings.title) {
+		window.set_title(&settings.title);
+	}
+	if changed(&|a| a.width != settings.width || a.height != settings.height) {
+		window.set_inner_size(inner_size(settings));
+	}
+	if changed(&|a| a.min_width != settings.min_width || a.min_height != settings.min_height) {
+		window.set_min_inner_size(min_inner_size(settings));
+	}
+	if changed(&|a| a.max_width != settings.max_width || a.max_height != settings.max_height) {
+		window.set_max_inner_size(max_inner_size(settings));
+	}
+	if changed(&|a| a.resizable != settings.resizable) {
+		window.set_resizable(settings.resizable);
+	}
+	*applied = Some(settings.clone());
 }
 
 impl <C: ComponentBase> ComponentWindow<C> {
-	pub fn new(window_builder: winit::window::WindowBuilder, component: C) -> Self {
+	pub fn new(component: C) -> Self {
+		Self::with_builder(WindowBuilder::new(), component)
+	}
+
+	// `window_builder` provides whatever a `window` root element doesn't set
+	pub fn with_builder(window_builder: WindowBuilder, component: C) -> Self {
 		let event_loop = EventLoop::new();
+		let component = Rc::new(RefCell::new(component));
+		let mut root = Element::root();
+
+		// the first update tells us how to open the window
+		ComponentBase::update(component.clone(), &mut root);
+		let settings = window_settings(&root).cloned();
+		let window_builder = match &settings {
+			Some(settings) => configure_window(window_builder, settings),
+			None => window_builder,
+		};
 		let window = window_builder.build(&event_loop).unwrap();
 
 		let instance = wgpu::Instance::new(wgpu::Backends::all());
@@ -256,13 +611,16 @@ impl <C: ComponentBase> ComponentWindow<C> {
 		
 		Self {
 			window,
+			settings,
 			background: wgpu::Color::WHITE,
 			surface,
 			event_loop,
 			instance,
-			component: Rc::new(RefCell::new(component)),
-			root: Element::root(),
+			component,
+			root,
 			pointer_position: (f32::INFINITY, f32::INFINITY),
+			drag: None,
+			images: Default::default(),
 		}
 	}
 
@@ -309,8 +667,29 @@ impl <C: ComponentBase> ComponentWindow<C> {
 
 			match event {
 				Event::WindowEvent { event: WindowEvent::CursorMoved { position, .. }, .. } => {
+					let last_position = self.pointer_position;
 					self.pointer_position =
 						(position.x as f32 / ctx.scale_factor, position.y as f32 / ctx.scale_factor);
+					if let Some(drag) = &mut self.drag {
+						let dx = last_position.0 - self.pointer_position.0;
+						let dy = last_position.1 - self.pointer_position.1;
+						if scroll_by(&mut self.root, &drag.scrolls, dx, dy) {
+							drag.moved = true;
+							self.window.request_redraw();
+						}
+					}
+				},
+				Event::WindowEvent { event: WindowEvent::MouseWheel { delta, .. }, .. } => {
+					let (dx, dy) = match delta {
+						winit::event::MouseScrollDelta::LineDelta(x, y) => (-x * 40.0, -y * 40.0),
+						winit::event::MouseScrollDelta::PixelDelta(p) =>
+							(-p.x as f32 / ctx.scale_factor, -p.y as f32 / ctx.scale_factor),
+					};
+					let mut scrolls = Vec::new();
+					find_scrolls_at_px_point(&self.root, self.pointer_position, &mut scrolls);
+					if scroll_by(&mut self.root, &scrolls, dx, dy) {
+						self.window.request_redraw();
+					}
 				},
 				Event::WindowEvent { event: WindowEvent::CursorEntered {..}, .. } => {
 					self.pointer_position = (f32::INFINITY, f32::INFINITY);
@@ -319,9 +698,19 @@ impl <C: ComponentBase> ComponentWindow<C> {
 					self.pointer_position = (f32::INFINITY, f32::INFINITY);
 				},
 				Event::WindowEvent { event: WindowEvent::MouseInput { state, button, .. }, .. } => {
-					if state == winit::event::ElementState::Released && button == winit::event::MouseButton::Left {
+					if state == winit::event::ElementState::Pressed && button == winit::event::MouseButton::Left {
+						let mut scrolls = Vec::new();
+						find_scrolls_at_px_point(&self.root, self.pointer_position, &mut scrolls);
+						if !scrolls.is_empty() {
+							self.drag = Some(Drag { scrolls, moved: false });
+						}
+					}
+					// releasing a drag doesn't count as a click
+					let dragged = state == winit::event::ElementState::Released
+						&& self.drag.take().map(|drag| drag.moved).unwrap_or(false);
+					if state == winit::event::ElementState::Released && button == winit::event::MouseButton::Left && !dragged {
 						let e = find_element_at_px_point(&self.root, self.pointer_position);
-						if let Some(callback) = &e.events.pointer_click {
+						if let Some(callback) = e.and_then(|e| e.events.pointer_click.as_ref()) {
 							callback.call();
 							self.window.request_redraw();
 						}
@@ -329,6 +718,9 @@ impl <C: ComponentBase> ComponentWindow<C> {
 				},
 				Event::RedrawRequested(_) => {
 					ComponentBase::update(self.component.clone(), &mut self.root);
+					update_window(&self.window, &mut self.settings, &self.root);
+					resolve_lengths(&mut self.root, &ctx.length_context());
+					arrange(&mut self.root);
 
 					let root_text_section = create_section(
 						RawBounds {
@@ -347,7 +739,8 @@ impl <C: ComponentBase> ComponentWindow<C> {
 						encoder: device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None }),
 						bufs: Vec::new(),
 						text_sections: vec![root_text_section],
-						font_styles: vec![FontStyle { color: Color { r: 0, g: 0, b: 0, a: 1.0 }, size: 35.0 }],
+						font_styles: vec![FontStyle { color: Color { r: 0, g: 0, b: 0, a: 1.0 }, size: FONT_SIZE }],
+						images: &mut self.images,
 					};
 
 					{
@@ -404,12 +797,18 @@ fn create_context<'a>(e: &'a Element, parent: &'a ElementContext) -> ElementCont
 		bounds.width = b.width;
 		bounds.height = b.height;
 	}
+	let opacity = match &e.element_impl {
+		ElementImpl::Rect(rect) | ElementImpl::Layout(Layout { rect, .. }) => parent.opacity * rect.opacity.clamp(0.0, 1.0),
+		_ => parent.opacity,
+	};
 	ElementContext {
 		parent: Some(parent),
 		scale_factor: parent.scale_factor,
 		vw: parent.vw,
 		vh: parent.vh,
 		bounds,
+		clip: parent.clip.clone(),
+		opacity,
 	}
 }
 
@@ -419,6 +818,11 @@ pub struct ElementContext<'a> {
 	pub vw: f32,
 	pub vh: f32,
 	pub bounds: PxBounds,
+	// the area outside of which nothing is drawn
+	pub clip: PxBounds,
+	// the product of the opacities of the element and its ancestors; unlike in css a subtree
+	// isn't composited first, so overlapping children show through each other
+	pub opacity: f32,
 }
 
 fn create_section(bounds: RawBounds) -> wgpu_text::section::OwnedSection {
@@ -453,6 +857,21 @@ impl <'a> ElementContext<'a> {
 			vh: height,
 			scale_factor,
 			bounds: PxBounds { x: 0.0, y: 0.0, width, height },
+			clip: PxBounds { x: 0.0, y: 0.0, width: width / scale_factor, height: height / scale_factor },
+			opacity: 1.0,
+		}
+	}
+
+	// `vw`/`vh` and `%` of the root refer to the window, in logical pixels
+	fn length_context(&self) -> LengthContext {
+		let width = self.vw / self.scale_factor;
+		let height = self.vh / self.scale_factor;
+		LengthContext {
+			width,
+			height,
+			font_size: FONT_SIZE,
+			viewport_width: width,
+			viewport_height: height,
 		}
 	}
 }
@@ -462,35 +881,29 @@ use bytemuck::{Zeroable, Pod};
 #[repr(C)]
 #[derive(Debug, Clone, Copy, Pod, Zeroable)]
 struct Vertex {
-	x: f32, y: f32, z: f32,
-	r: f32, g: f32, b: f32, a: f32,
+	x: f32, y: f32,
+	local_x: f32, local_y: f32,
+	half_width: f32, half_height: f32,
+	radius: f32, border: f32, softness: f32, gradient: f32,
+	color: [f32; 4],
+	border_color: [f32; 4],
 }
 
 impl Vertex {
+	const ATTRIBUTES: [wgpu::VertexAttribute; 6] = wgpu::vertex_attr_array![
+		0 => Float32x2,
+		1 => Float32x2,
+		2 => Float32x2,
+		3 => Float32x4,
+		4 => Float32x4,
+		5 => Float32x4,
+	];
+
 	fn layout<'a>() -> wgpu::VertexBufferLayout<'a> {
 		wgpu::VertexBufferLayout {
 			array_stride: std::mem::size_of::<Vertex>() as wgpu::BufferAddress,
 			step_mode: wgpu::VertexStepMode::Vertex,
-			attributes: &[
-				wgpu::VertexAttribute {
-					offset: 0,
-					shader_location: 0,
-					format: wgpu::VertexFormat::Float32x3,
-				},
-				wgpu::VertexAttribute {
-					offset: (std::mem::size_of::<f32>() * 3) as wgpu::BufferAddress,
-					shader_location: 1,
-					format: wgpu::VertexFormat::Float32x4,
-				},
-			]
+			attributes: &Self::ATTRIBUTES,
 		}
 	}
-}
-
-fn vertices(r: f32, g: f32, b: f32, a: f32, points: &[(f32,f32)]) -> Vec<Vertex> {
-	points.iter().map(|&(x,y)| Vertex {
-		x, y, z: 0.0,
-		r, g, b, a,
-	})
-	.collect()
 }
\ No newline at end of file
@@ -1,11 +1,19 @@
 struct VertexInput {
-    [[location(0)]] position: vec3<f32>;
-    [[location(1)]] color: vec4<f32>;
+    [[location(0)]] position: vec2<f32>;
+    [[location(1)]] local: vec2<f32>;
+    [[location(2)]] half_size: vec2<f32>;
+    [[location(3)]] shape: vec4<f32>;
+    [[location(4)]] color: vec4<f32>;
+    [[location(5)]] border_color: vec4<f32>;
 };
 
 struct VertexOutput {
     [[builtin(position)]] clip_position: vec4<f32>;
-    [[location(0)]] color: vec4<f32>;
+    [[location(0)]] local: vec2<f32>;
+    [[location(1)]] half_size: vec2<f32>;
+    [[location(2)]] shape: vec4<f32>;
+    [[location(3)]] color: vec4<f32>;
+    [[location(4)]] border_color: vec4<f32>;
 };
 
 [[stage(vertex)]]
@@ -13,7 +21,11 @@ fn vs_main(
     model: VertexInput,
 ) -> VertexOutput {
     var out: VertexOutput;
+    out.local = model.local;
+    out.half_size = model.half_size;
+    out.shape = model.shape;
     out.color = model.color;
-    out.clip_position = vec4<f32>(model.position, 1.0);
+    out.border_color = model.border_color;
+    out.clip_position = vec4<f32>(model.position, 0.0, 1.0);
     return out;
-}
\ No newline at end of file
+}
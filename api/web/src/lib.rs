@@ -1,9 +1,12 @@
 use std::{
 	collections::HashMap,
 	rc::Rc,
-	alloc::{alloc, dealloc, Layout},
+	alloc::{self, alloc, dealloc},
+	sync::LazyLock,
 };
 
+use regex::Regex;
+
 pub use ui_base::*;
 
 #[derive(Default)]
@@ -27,8 +30,17 @@ extern "C" {
 	fn __first_child(node: HtmlNode) -> HtmlNode;
 	fn __remove(node: HtmlNode);
 	fn __set_text_content(node: HtmlNode, ptr: *const u8, len: usize);
+	fn __set_document_title(ptr: *const u8, len: usize);
 	fn __set_style(node: HtmlNode, pptr: *const u8, plen: usize, vptr: *const u8, vlen: usize);
+	fn __set_attribute(node: HtmlNode, nptr: *const u8, nlen: usize, vptr: *const u8, vlen: usize);
 	fn __update_event_listener(node: HtmlNode, event: *const u8, event_len: usize, callback: usize, heap_ref: JsValue);
+	fn __set_scroll_listener(node: HtmlNode, listener: usize, heap_ref: JsValue);
+	fn __set_scroll_offset(node: HtmlNode, x: f32, y: f32);
+	fn __client_width(node: HtmlNode) -> f32;
+	fn __client_height(node: HtmlNode) -> f32;
+	fn __font_size(node: HtmlNode) -> f32;
+	fn __viewport_width() -> f32;
+	fn __viewport_height() -> f32;
 	fn __heap_object_as_bool(object: JsValue) -> isize;
 	fn __heap_object_stage_string(object: JsValue) -> isize;
 	fn __heap_object_load_string(dest: *const u8);
@@ -42,6 +54,8 @@ extern "C" {
 	fn __send_f32(value: f32) -> JsValue;
 	fn __new_array() -> JsValue;
 	fn __array_push(object: JsValue, value: JsValue);
+	fn __new_object() -> JsValue;
+	fn __object_set_property(object: JsValue, keyptr: *const u8, keylen: usize, value: JsValue);
 	pub fn __send_string(ptr: *const u8, len: usize) -> JsValue;
 }
 
@@ -54,6 +68,22 @@ pub fn __dispatch_bound_callback(ptr: usize) {
 	}
 }
 
+#[no_mangle]
+pub fn __dispatch_scroll(ptr: usize, x: f32, y: f32) {
+	let listener = unsafe { &*(ptr as *const ScrollListener) };
+	listener.binding.set(Length::Px(x), Length::Px(y));
+	if let Some(callback) = &listener.callback {
+		callback.call();
+	}
+}
+
+// what a `scroll` node reports to when the user scrolls it
+#[derive(Debug, Default)]
+pub struct ScrollListener {
+	binding: ScrollBinding,
+	callback: Option<BoundCallback>,
+}
+
 #[repr(transparent)]
 #[derive(Debug)]
 pub struct HtmlNode(pub usize);
@@ -99,11 +129,36 @@ impl HtmlNode {
 			});
 		});
 	}
+	pub fn set_attribute<S1: AsRef<str>, S2: AsRef<str>>(&self, name: S1, value: S2) {
+		string_into_js(&name, |np, nlen| {
+			string_into_js(&value, |vp, vlen| {
+				unsafe { __set_attribute(HtmlNode(self.0), np, nlen, vp, vlen) }
+			});
+		});
+	}
 	pub fn update_event_listener(&self, event: &str, callback: usize, heap_ref: &JsValue) {
 		string_into_js(&event, |p, len| unsafe {
 			__update_event_listener(HtmlNode(self.0), p, len, callback, JsValue(heap_ref.0));
 		});
 	}
+	pub fn set_scroll_listener(&self, listener: &ScrollListener, heap_ref: &JsValue) {
+		unsafe { __set_scroll_listener(HtmlNode(self.0), listener as *const ScrollListener as usize, JsValue(heap_ref.0)) }
+	}
+	pub fn set_scroll_offset(&self, x: f32, y: f32) {
+		unsafe { __set_scroll_offset(HtmlNode(self.0), x, y) }
+	}
+	// `%` of a component's root refers to the element it is attached to, `vw`/`vh` to the window
+	pub fn length_context(&self) -> LengthContext {
+		unsafe {
+			LengthContext {
+				width: __client_width(HtmlNode(self.0)),
+				height: __client_height(HtmlNode(self.0)),
+				font_size: __font_size(HtmlNode(self.0)),
+				viewport_width: __viewport_width(),
+				viewport_height: __viewport_height(),
+			}
+		}
+	}
 }
 
 impl Drop for HtmlNode {
@@ -120,7 +175,7 @@ pub fn string_into_js<S: AsRef<str>, T, F: FnOnce(*const u8, usize) -> T>(s: S,
 		return f(0 as *const u8, 0);
 	}
 	let size = s.len();
-	let layout = Layout::array::<u8>(size).unwrap();
+	let layout = alloc::Layout::array::<u8>(size).unwrap();
 	unsafe {
 		let buf = alloc(layout);
 		let s = &s.as_bytes()[0];
@@ -143,6 +198,10 @@ fn create_text_node<S: AsRef<str>>(content: S) -> HtmlNode {
 	string_into_js(&content, |p, len| unsafe { __create_text_node(p, len) })
 }
 
+fn set_document_title<S: AsRef<str>>(title: S) {
+	string_into_js(&title, |p, len| unsafe { __set_document_title(p, len) });
+}
+
 fn create_element<S: AsRef<str>>(tag: S) -> HtmlNode {
 	string_into_js(&tag, |p, len| unsafe { __create_element(p, len) })
 }
@@ -189,7 +248,7 @@ impl JsValue {
 		}
 
 		let size = size as usize;
-		let layout = Layout::array::<u8>(size).unwrap();
+		let layout = alloc::Layout::array::<u8>(size).unwrap();
 		unsafe {
 			let buf = alloc(layout);
 			__heap_object_load_string(buf);
@@ -229,6 +288,12 @@ impl JsValue {
 	pub fn array_push(&self, value: JsValue) {
 		unsafe { __array_push(JsValue(self.0), JsValue(value.0)) }
 	}
+	pub fn new_object() -> JsValue {
+		unsafe { __new_object() }
+	}
+	pub fn set_property<S: AsRef<str>>(&self, key: S, value: JsValue) {
+		string_into_js(&key, |p, len| unsafe { __object_set_property(JsValue(self.0), p, len, JsValue(value.0)) });
+	}
 	pub fn get_property<S: AsRef<str>>(&self, key: S) -> Option<JsValue> {
 		let result = string_into_js(&key, |p, len| unsafe { __heap_object_get_property(JsValue(self.0), p, len) });
 		if result.0 == 0 {
@@ -273,6 +338,31 @@ fn length_as_css(this: &Length) -> String {
 		Length::In(nn) => format!("{nn}in"),
 		Length::Cm(cm) => format!("{cm}cm"),
 		Length::Mm(mm) => format!("{mm}mm"),
+		Length::Pt(pt) => format!("{pt}pt"),
+		Length::Percent(n) => format!("{n}%"),
+		Length::Em(em) => format!("{em}em"),
+		Length::Vw(vw) => format!("{vw}vw"),
+		Length::Vh(vh) => format!("{vh}vh"),
+		Length::Calc(Calc { px, percent, em, vw, vh }) => {
+			let terms = [(px, "px"), (percent, "%"), (em, "em"), (vw, "vw"), (vh, "vh")];
+			let terms: Vec<_> = terms.iter()
+				.filter(|(n, _)| **n != 0.0)
+				.map(|(n, unit)| format!("{n}{unit}"))
+				.collect();
+			if terms.is_empty() {
+				String::from("0px")
+			} else {
+				format!("calc({})", terms.join(" + "))
+			}
+		},
+	}
+}
+
+fn fit_as_css(this: &Fit) -> &'static str {
+	match this {
+		Fit::Contain => "contain",
+		Fit::Cover => "cover",
+		Fit::Fill => "fill",
 	}
 }
 
@@ -281,6 +371,27 @@ fn color_as_css(this: &Color) -> String {
 	format!("rgba({r},{g},{b},{a})")
 }
 
+fn brush_as_css(this: &Brush) -> String {
+	let stops = || this.stops().iter().map(|stop| match stop.position {
+		Some(position) => format!(", {} {}%", color_as_css(&stop.color), position * 100.0),
+		None => format!(", {}", color_as_css(&stop.color)),
+	})
+	.collect::<String>();
+	match this {
+		Brush::Color(color) => color_as_css(color),
+		Brush::Linear { angle, .. } => format!("linear-gradient({angle}deg{})", stops()),
+		Brush::Radial { .. } => format!("radial-gradient(farthest-corner{})", stops()),
+	}
+}
+
+fn shadow_as_css(this: &Shadow) -> String {
+	let color = this.color.fallback_color();
+	if color.a <= 0.0 {
+		return "none".to_owned();
+	}
+	format!("{} {} {} {}", length_as_css(&this.x), length_as_css(&this.y), length_as_css(&this.blur), color_as_css(&color))
+}
+
 pub trait AsJsValue {
 	fn as_js_value(&self) -> JsValue;
 }
@@ -304,20 +415,16 @@ impl AsJsValue for Length {
 		JsValue::from_str(&length_as_css(self))
 	}
 }
+// a single length, or `calc()` with terms joined by `+` or `-` the way `length_as_css` writes them
+static LENGTH: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^(-?(?:\d+(?:\.\d+)?|\.\d+))(px|in|cm|mm|pt|%|em|vw|vh)$").unwrap());
+static CALC: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^calc\((.*)\)$").unwrap());
+static CALC_TERM: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\s*([+-])?\s*(-?(?:\d+(?:\.\d+)?|\.\d+))(px|in|cm|mm|pt|%|em|vw|vh)").unwrap());
+
 impl FromJsValue for Length {
 	fn from_js_value(value: JsValue) -> Length {
-		use regex::Regex;
 		if let Some(s) = value.as_string() {
-			let re = Regex::new(r"^(\d+(?:\.\d+)?|\.\d+)(px|in|cm|mm)$").unwrap();
-			if let Some(captures) = re.captures(&s) {
-				let f = str::parse::<f32>(&captures[1]).unwrap();
-				return match &captures[2] {
-					"px" => Length::Px(f),
-					"in" => Length::In(f),
-					"cm" => Length::Cm(f),
-					"mm" => Length::Mm(f),
-					_ => unreachable!(),
-				}
+			if let Some(length) = parse_length(&s) {
+				return length;
 			}
 		} else if let Some(f) = value.as_f32() {
 			return Length::Px(f);
@@ -326,6 +433,138 @@ impl FromJsValue for Length {
 	}
 }
 
+fn parse_length(s: &str) -> Option<Length> {
+	if let Some(captures) = LENGTH.captures(s) {
+		let f = str::parse::<f32>(&captures[1]).unwrap();
+		return Some(unit_length(f, &captures[2]));
+	}
+	let mut rest = CALC.captures(s)?.get(1).unwrap().as_str();
+	let mut sum = Length::Px(0.0);
+	while let Some(captures) = CALC_TERM.captures(rest) {
+		let f = str::parse::<f32>(&captures[2]).unwrap();
+		let length = unit_length(f, &captures[3]);
+		sum = match captures.get(1).map(|m| m.as_str()) {
+			Some("-") => sum - length,
+			_ => sum + length,
+		};
+		rest = &rest[captures.get(0).unwrap().end()..];
+	}
+	if rest.trim().is_empty() {
+		Some(sum)
+	} else {
+		None
+	}
+}
+
+fn unit_length(f: f32, unit: &str) -> Length {
+	match unit {
+		"px" => Length::Px(f),
+		"in" => Length::In(f),
+		"cm" => Length::Cm(f),
+		"mm" => Length::Mm(f),
+		"pt" => Length::Pt(f),
+		"%" => Length::Percent(f),
+		"em" => Length::Em(f),
+		"vw" => Length::Vw(f),
+		"vh" => Length::Vh(f),
+		_ => unreachable!(),
+	}
+}
+
+impl AsJsValue for Fit {
+	fn as_js_value(&self) -> JsValue {
+		JsValue::from_str(fit_as_css(self))
+	}
+}
+impl FromJsValue for Fit {
+	fn from_js_value(value: JsValue) -> Fit {
+		match value.as_string().as_deref() {
+			Some("contain") => Fit::Contain,
+			Some("cover") => Fit::Cover,
+			_ => Fit::Fill,
+		}
+	}
+}
+
+impl AsJsValue for Color {
+	fn as_js_value(&self) -> JsValue {
+		let object = JsValue::new_object();
+		object.set_property("r", JsValue::from_f32(self.r as f32));
+		object.set_property("g", JsValue::from_f32(self.g as f32));
+		object.set_property("b", JsValue::from_f32(self.b as f32));
+		object.set_property("a", self.a.as_js_value());
+		object
+	}
+}
+impl FromJsValue for Color {
+	fn from_js_value(value: JsValue) -> Color {
+		if let Some(s) = value.as_string() {
+			return Color::parse(&s).unwrap_or_default();
+		}
+		let channel = |key| value.get_property(key).and_then(|e| e.as_f32());
+		Color {
+			r: channel("r").unwrap_or_default() as u8,
+			g: channel("g").unwrap_or_default() as u8,
+			b: channel("b").unwrap_or_default() as u8,
+			a: channel("a").unwrap_or(1.0),
+		}
+	}
+}
+
+// in the shape `TYPE_SANITIZERS.Brush` in ui.js turns brushes set from js into
+impl AsJsValue for Brush {
+	fn as_js_value(&self) -> JsValue {
+		let object = JsValue::new_object();
+		let stops = || self.stops().iter().fold(JsValue::new_array(), |acc, stop| {
+			let e = JsValue::new_object();
+			e.set_property("color", stop.color.as_js_value());
+			if let Some(position) = stop.position {
+				e.set_property("position", position.as_js_value());
+			}
+			acc.array_push(e);
+			acc
+		});
+		match self {
+			Brush::Color(color) => {
+				object.set_property("type", JsValue::from_str("color"));
+				object.set_property("color", color.as_js_value());
+			},
+			Brush::Linear { angle, .. } => {
+				object.set_property("type", JsValue::from_str("linear"));
+				object.set_property("angle", angle.as_js_value());
+				object.set_property("stops", stops());
+			},
+			Brush::Radial { .. } => {
+				object.set_property("type", JsValue::from_str("radial"));
+				object.set_property("stops", stops());
+			},
+		}
+		object
+	}
+}
+impl FromJsValue for Brush {
+	fn from_js_value(value: JsValue) -> Brush {
+		if let Some(s) = value.as_string() {
+			return Brush::Color(Color::parse(&s).unwrap_or_default());
+		}
+		let stops = || value.get_property("stops")
+			.and_then(|e| e.into_iter())
+			.map(|iter| iter.map(|e| ColorStop {
+				color: e.get_property("color").map(Color::from_js_value).unwrap_or_default(),
+				position: e.get_property("position").and_then(|e| e.as_f32()),
+			})
+			.collect())
+			.unwrap_or_default();
+		let angle = value.get_property("angle").and_then(|e| e.as_f32()).unwrap_or_default();
+		match value.get_property("type").and_then(|e| e.as_string()).as_deref() {
+			Some("color") => value.get_property("color").map(|e| Brush::Color(Color::from_js_value(e))).unwrap_or_default(),
+			Some("linear") => Brush::Linear { angle, stops: stops() },
+			Some("radial") => Brush::Radial { stops: stops() },
+			_ => Brush::default(),
+		}
+	}
+}
+
 impl AsJsValue for bool {
 	fn as_js_value(&self) -> JsValue {
 		JsValue::from_bool(*self)
@@ -359,54 +598,104 @@ impl FromJsValue for i32 {
 	}
 }
 
-impl <T: AsJsValue + std::fmt::Debug> AsJsValue for Iterable<T> {
+impl AsJsValue for f32 {
 	fn as_js_value(&self) -> JsValue {
-		match self {
-			Iterable::Int(n) => n.as_js_value(),
-			Iterable::Array(a) => {
-				a.iter().fold(
-					JsValue::new_array(),
-					|acc, e| { acc.array_push(e.as_js_value()); acc }
-				)
-			},
-		}
+		JsValue::from_f32(*self)
+	}
+}
+impl FromJsValue for f32 {
+	fn from_js_value(value: JsValue) -> f32 {
+		value.as_f32().unwrap_or_default()
 	}
 }
+
+impl <T: AsJsValue> AsJsValue for [T] {
+	fn as_js_value(&self) -> JsValue {
+		self.iter().fold(
+			JsValue::new_array(),
+			|acc, e| { acc.array_push(e.as_js_value()); acc }
+		)
+	}
+}
+
+impl <T: AsJsValue> AsJsValue for Iterable<T> {
+	fn as_js_value(&self) -> JsValue {
+		self.as_slice().as_js_value()
+	}
+}
+// a number `n` stands for the list of the numbers up to it
 impl <T: FromJsValue> FromJsValue for Iterable<T> {
 	fn from_js_value(value: JsValue) -> Iterable<T> {
-		if let Some(n) = value.as_f32() {
-			<Iterable<T>>::Int(n as i32)
+		let vector: Vec<T> = if let Some(n) = value.as_f32() {
+			(0..n as i32).map(|i| T::from_js_value(JsValue::from_f32(i as f32))).collect()
 		} else if let Some(iter) = value.into_iter() {
-			let vector = iter.fold(
-				Vec::new(),
-				|mut acc, e| { acc.push(T::from_js_value(e)); acc }
-			);
-			Iterable::Array(vector)
+			iter.map(T::from_js_value).collect()
 		} else {
-			Iterable::Int(0)
-		}
+			Vec::new()
+		};
+		vector.into()
 	}
 }
 
 #[derive(Debug)]
 pub struct WebElement {
+	// the id of the element this mirrors, so that it can follow it when a keyed repeater moves it
+	pub id: Option<usize>,
 	pub node: Option<Rc<HtmlNode>>,
 	pub events: HashMap<String, BoundCallback>,
-	pub active_group: Option<usize>,
+	// indices of the (possibly nested) groups currently being rendered into
+	pub active_group: Vec<usize>,
 	pub children: Vec<WebElement>,
 	pub is_in: bool,
+	// set when moved among its siblings, so that its node is put back in order
+	pub moved: bool,
+	// what was last written to the node, so that writes that wouldn't change anything are skipped
+	pub styles: HashMap<String, String>,
+	pub attributes: HashMap<String, String>,
+	pub text: Option<String>,
 	pub last_in: Option<Rc<HtmlNode>>,
+	// boxed so that the node's listener can keep pointing at it
+	pub scroll_listener: Option<Box<ScrollListener>>,
 }
 
 impl WebElement {
 	pub fn new(e: Option<Rc<HtmlNode>>) -> WebElement {
 		WebElement {
+			id: None,
 			node: e,
 			events: HashMap::new(),
-			active_group: None,
+			active_group: Vec::new(),
 			children: Vec::new(),
 			is_in: false,
+			moved: false,
+			styles: HashMap::new(),
+			attributes: HashMap::new(),
+			text: None,
 			last_in: None,
+			scroll_listener: None,
+		}
+	}
+}
+
+impl WebElement {
+	pub fn set_style<S: AsRef<str>>(&mut self, property: &str, value: S) {
+		let value = value.as_ref();
+		if self.styles.get(property).map(String::as_str) != Some(value) {
+			self.node.as_ref().unwrap().set_style(property, value);
+			self.styles.insert(property.to_owned(), value.to_owned());
+		}
+	}
+	pub fn set_attribute<S: AsRef<str>>(&mut self, name: &str, value: S) {
+		let value = value.as_ref();
+		if self.attributes.get(name).map(String::as_str) != Some(value) {
+			self.node.as_ref().unwrap().set_attribute(name, value);
+			self.attributes.insert(name.to_owned(), value.to_owned());
+		}
+	}
+	pub fn set_text_content(&mut self, content: &str) {
+		if self.text.as_deref() != Some(content) {
+			self.node.as_ref().unwrap().set_text_content(content);
+			self.text = Some(content.to_owned());
 		}
 	}
 }
@@ -419,6 +708,11 @@ pub trait RenderWeb {
 
 impl RenderWeb for Element {
 	fn render<'a>(&mut self, parent: &'a mut WebElement, i: usize, _show: bool, heap_ref: &JsValue) -> Option<&'a mut WebElement> {
+		// the root and the window render straight into their parent
+		let has_slot = !matches!(self.element_impl, ElementImpl::Root(..)|ElementImpl::Window(_));
+		if has_slot {
+			web_element_in(parent, i, self.id());
+		}
 		if let Some(mut parent) = RenderWeb::render(&mut self.element_impl, parent, i, self.show, heap_ref) {
 			if let Some(callback) = self.events.pointer_click.as_ref() {
 				let node = parent.node.as_ref().unwrap();
@@ -429,13 +723,39 @@ impl RenderWeb for Element {
 					parent.events.insert("click".into(), callback);
 				}
 			}
+			if let ElementImpl::Scroll(scroll) = &self.element_impl {
+				let listener = ScrollListener {
+					binding: scroll.binding.clone(),
+					callback: self.events.scroll.clone(),
+				};
+				match parent.scroll_listener.as_mut() {
+					Some(current) => **current = listener,
+					None => {
+						let listener = Box::new(listener);
+						parent.node.as_ref().unwrap().set_scroll_listener(&listener, heap_ref);
+						parent.scroll_listener = Some(listener);
+					},
+				}
+			}
 			if self.group {
 				group_in(parent, i);
 			}
 			for (i, e) in self.children.iter_mut().enumerate() {
 				e.render(&mut parent, i, e.show, heap_ref);
 			}
-			parent.active_group = None;
+			if has_slot {
+				remove_web_elements(parent, self.children.len());
+			}
+			if self.group {
+				parent.active_group.pop();
+			}
+			// only once the content is in place can it be scrolled to
+			if let ElementImpl::Scroll(scroll) = &self.element_impl {
+				if self.show && (scroll.offset_x.is_some() || scroll.offset_y.is_some()) {
+					let (x, y) = scroll.offset();
+					parent.node.as_ref().unwrap().set_scroll_offset(x, y);
+				}
+			}
 			Some(parent)
 		} else {
 			None
@@ -447,7 +767,15 @@ impl RenderWeb for ElementImpl {
 	fn render<'a>(&mut self, parent: &'a mut WebElement, i: usize, show: bool, heap_ref: &JsValue) -> Option<&'a mut WebElement> {
 		match self {
 			ElementImpl::Root(..)|ElementImpl::Group => Some(parent),
+			// the page is the window, so only the title applies
+			ElementImpl::Window(window) => {
+				set_document_title(&window.title);
+				Some(parent)
+			},
 			ElementImpl::Rect(rect) => RenderWeb::render(rect, parent, i, show, heap_ref),
+			ElementImpl::Layout(layout) => RenderWeb::render(&mut layout.rect, parent, i, show, heap_ref),
+			ElementImpl::Scroll(scroll) => RenderWeb::render(scroll, parent, i, show, heap_ref),
+			ElementImpl::Img(img) => RenderWeb::render(img, parent, i, show, heap_ref),
 			ElementImpl::Span(span) => RenderWeb::render(span, parent, i, show, heap_ref),
 			ElementImpl::Text(text) => RenderWeb::render(text, parent, i, show, heap_ref),
 		}
@@ -455,50 +783,92 @@ impl RenderWeb for ElementImpl {
 }
 
 fn group_in<'a>(parent: &'a mut WebElement, i: usize) {
-	if parent.children.len() == i {
-		parent.children.push(WebElement::new(None))
-	} else if i > parent.children.len() {
-		console_log("i > parent.children.len() this should never happen!");
+	let group = &mut active_children(parent)[i];
+	// a group has no node of its own, so moving it moves each of its children
+	if group.moved {
+		group.moved = false;
+		for e in group.children.iter_mut() {
+			e.moved = true;
+		}
+	}
+	parent.active_group.push(i);
+}
+
+// lines up the web element at `i` with the element `id`, moving it there from further on or
+// starting a new one
+fn web_element_in(parent: &mut WebElement, i: usize, id: usize) {
+	let children = active_children(parent);
+	if i > children.len() {
+		console_log(format!("i > children.len() ({} > {}) this should never happen!", i, children.len()));
 		panic!();
 	}
-	parent.active_group = Some(i);
+	match children[i..].iter().position(|e| e.id == Some(id)) {
+		Some(0) => {},
+		Some(j) => {
+			let mut e = children.remove(i + j);
+			e.moved = true;
+			children.insert(i, e);
+		},
+		None => {
+			let mut e = WebElement::new(None);
+			e.id = Some(id);
+			children.insert(i, e);
+		},
+	}
+}
+
+// drops the web elements past `len`, whose elements are gone, along with their nodes
+fn remove_web_elements(parent: &mut WebElement, len: usize) {
+	let children = active_children(parent);
+	if children.len() > len {
+		for e in children.drain(len..) {
+			remove_nodes(&e);
+		}
+	}
+}
+
+fn remove_nodes(e: &WebElement) {
+	match e.node.as_ref() {
+		Some(node) => if e.is_in {
+			node.remove();
+		},
+		None => for e in e.children.iter() {
+			remove_nodes(e);
+		},
+	}
+}
+
+fn active_children(parent: &mut WebElement) -> &mut Vec<WebElement> {
+	let mut children = &mut parent.children;
+	for &group_index in parent.active_group.iter() {
+		children = &mut children[group_index].children;
+	}
+	children
 }
 
 fn get_web_element<'a>(parent: &'a mut WebElement, i: usize) -> &'a mut WebElement {
 	assert!(parent.node.is_some());
 
-	let children = if let Some(group_index) = parent.active_group {
-		&mut parent.children[group_index].children
-	} else {
-		&mut parent.children
-	};
-	&mut children[i]
+	&mut active_children(parent)[i]
 }
 
 fn get_html<'a>(parent: &'a mut WebElement, tag_or_content: &str, i: usize, is_text: bool) -> &'a mut WebElement {
 	assert!(parent.node.is_some());
 
-	let children = if let Some(group_index) = parent.active_group {
-		&mut parent.children[group_index].children
-	} else {
-		&mut parent.children
-	};
-	if children.len() == i {
-		let e = if is_text {
+	let e = &mut active_children(parent)[i];
+	if e.node.is_none() {
+		let node = if is_text {
+			e.text = Some(tag_or_content.to_owned());
 			create_text_node(tag_or_content)
 		} else {
 			create_element(tag_or_content)
 		};
-		children.push(WebElement::new(Some(Rc::new(e))))
-	} else if i > children.len() {
-		console_log(format!("i > children.len() ({} > {}) this should never happen!", i, children.len()));
-		panic!();
+		e.node = Some(Rc::new(node));
 	}
-
-	&mut children[i]
+	e
 }
 
-fn html_in(parent: &mut WebElement, tag_or_content: &str, i: usize, is_text: bool) -> Rc<HtmlNode> {
+fn html_in<'a>(parent: &'a mut WebElement, tag_or_content: &str, i: usize, is_text: bool) -> &'a mut WebElement {
 	let last_in = parent.last_in.clone();
 	let parent_node = parent.node.clone().unwrap();
 	
@@ -506,7 +876,7 @@ fn html_in(parent: &mut WebElement, tag_or_content: &str, i: usize, is_text: boo
 		let e = get_html(parent, tag_or_content, i, is_text);
 
 		e.last_in = None;
-		if !e.is_in {
+		if !e.is_in || e.moved {
 			if let Some(l) = last_in {
 				if let Some(sibling) = l.next_sibling() {
 					parent_node.insert_before(e.node.as_ref().unwrap(), Some(&sibling));
@@ -518,11 +888,12 @@ fn html_in(parent: &mut WebElement, tag_or_content: &str, i: usize, is_text: boo
 			}
 		}
 		e.is_in = true;
+		e.moved = false;
 		e.node.clone()
 	};
 
-	parent.last_in = node.clone();
-	node.unwrap()
+	parent.last_in = node;
+	get_web_element(parent, i)
 }
 
 fn html_out(parent: &mut WebElement, tag: &str, i: usize, is_text: bool) {
@@ -537,7 +908,7 @@ fn html_out(parent: &mut WebElement, tag: &str, i: usize, is_text: bool) {
 	}
 }
 
-fn html_element_in(parent: &mut WebElement, tag: &str, i: usize) -> Rc<HtmlNode> {
+fn html_element_in<'a>(parent: &'a mut WebElement, tag: &str, i: usize) -> &'a mut WebElement {
 	html_in(parent, tag, i, false)
 }
 
@@ -545,7 +916,7 @@ fn html_element_out(parent: &mut WebElement, tag: &str, i: usize) {
 	html_out(parent, tag, i, false);
 }
 
-fn html_text_in(parent: &mut WebElement, content: &str, i: usize) -> Rc<HtmlNode> {
+fn html_text_in<'a>(parent: &'a mut WebElement, content: &str, i: usize) -> &'a mut WebElement {
 	let text = html_in(parent, content, i, true);
 	text.set_text_content(content);
 	text
@@ -559,12 +930,14 @@ impl RenderWeb for Rect {
 	fn render<'a>(&mut self, parent: &'a mut WebElement, i: usize, show: bool, _heap_ref: &JsValue) -> Option<&'a mut WebElement> {
 		if show {
 			let e = html_element_in(parent, "div", i);
-			let r = self.color.r;
-			let g = self.color.g;
-			let b = self.color.b;
-			let a = self.color.a;
 			e.set_style("position", "absolute");
-			e.set_style("background", &format!("rgba({},{},{},{})", r,g,b,a));
+			e.set_style("boxSizing", "border-box");
+			e.set_style("background", brush_as_css(&self.color));
+			e.set_style("border", format!("{} solid {}", length_as_css(&self.border.width), color_as_css(&self.border.color.fallback_color())));
+			e.set_style("overflow", if self.clip { "hidden" } else { "visible" });
+			e.set_style("borderRadius", length_as_css(&self.radius));
+			e.set_style("boxShadow", shadow_as_css(&self.shadow));
+			e.set_style("opacity", self.opacity.to_string());
 			e.set_style("width", length_as_css(&self.bounds.width));
 			e.set_style("height", length_as_css(&self.bounds.height));
 			e.set_style("left", length_as_css(&self.bounds.x));
@@ -576,6 +949,41 @@ impl RenderWeb for Rect {
 	}
 }
 
+impl RenderWeb for Scroll {
+	fn render<'a>(&mut self, parent: &'a mut WebElement, i: usize, show: bool, _heap_ref: &JsValue) -> Option<&'a mut WebElement> {
+		if show {
+			let e = html_element_in(parent, "div", i);
+			e.set_style("position", "absolute");
+			e.set_style("overflow", "auto");
+			e.set_style("width", length_as_css(&self.bounds.width));
+			e.set_style("height", length_as_css(&self.bounds.height));
+			e.set_style("left", length_as_css(&self.bounds.x));
+			e.set_style("top", length_as_css(&self.bounds.y));
+		} else {
+			html_element_out(parent, "div", i);
+		}
+		Some(get_web_element(parent, i))
+	}
+}
+
+impl RenderWeb for Img {
+	fn render<'a>(&mut self, parent: &'a mut WebElement, i: usize, show: bool, _heap_ref: &JsValue) -> Option<&'a mut WebElement> {
+		if show {
+			let e = html_element_in(parent, "img", i);
+			e.set_attribute("src", &self.src);
+			e.set_style("position", "absolute");
+			e.set_style("objectFit", fit_as_css(&self.fit));
+			e.set_style("width", length_as_css(&self.bounds.width));
+			e.set_style("height", length_as_css(&self.bounds.height));
+			e.set_style("left", length_as_css(&self.bounds.x));
+			e.set_style("top", length_as_css(&self.bounds.y));
+		} else {
+			html_element_out(parent, "img", i);
+		}
+		Some(get_web_element(parent, i))
+	}
+}
+
 impl RenderWeb for Span {
 	fn render<'a>(&mut self, parent: &'a mut WebElement, i: usize, show: bool, _heap_ref: &JsValue) -> Option<&'a mut WebElement> {
 		if show {
@@ -585,7 +993,7 @@ impl RenderWeb for Span {
 			}
 			e.set_style("left", length_as_css(&self.x));
 			e.set_style("top", length_as_css(&self.y));
-			e.set_style("color", color_as_css(&self.color));
+			e.set_style("color", color_as_css(&self.color.fallback_color()));
 		} else {
 			html_element_out(parent, "span", i);
 		}
@@ -605,5 +1013,28 @@ impl RenderWeb for Text {
 }
 
 pub fn render_html(component_heap_ref: &JsValue, root: &mut Element, web_element: &mut WebElement) {
+	// layout only works in pixels, so relative lengths are resolved first as on native
+	if let Some(node) = web_element.node.as_ref() {
+		resolve_lengths(root, &node.length_context());
+	}
+	arrange(root);
 	RenderWeb::render(root, web_element, 0, true, component_heap_ref);
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parses_lengths_as_css_writes_them() {
+		assert_eq!(parse_length("10px"), Some(Length::Px(10.0)));
+		assert_eq!(parse_length("-10px"), Some(Length::Px(-10.0)));
+		assert!(matches!(parse_length(".5em"), Some(Length::Em(n)) if n == 0.5));
+		assert!(matches!(parse_length("-2.5vw"), Some(Length::Vw(n)) if n == -2.5));
+		assert_eq!(parse_length("calc(50% - 10px)"), Some(Length::Percent(50.0) - Length::Px(10.0)));
+		assert_eq!(parse_length("calc(-5px + 1em)"), Some(Length::Px(-5.0) + Length::Em(1.0)));
+		assert_eq!(parse_length("10"), None);
+		assert_eq!(parse_length("- 10px"), None);
+		assert_eq!(parse_length("calc(50% * 2)"), None);
+	}
+}
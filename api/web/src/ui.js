@@ -27,6 +27,17 @@
 					}
 					arr.push(uiPriv.getHeapObject(vptr));
 				},
+				__new_object() {
+					return uiPriv.addToHeap({});
+				},
+				__object_set_property(ptr, keyptr, keylen, vptr) {
+					const object = uiPriv.getHeapObject(ptr);
+					if(!(object instanceof Object)) {
+						throw new Error(`expected Object, found '${object}'`)
+					}
+					const key = uiPriv.getStringFromWasm(keyptr, keylen);
+					object[key] = uiPriv.getHeapObject(vptr);
+				},
 				__console_log(ptr, len) {
 					const message = uiPriv.getStringFromWasm(ptr, len);
 					console.log(message);
@@ -65,11 +76,25 @@
 				__set_text_content(node, ptr, len) {
 					uiPriv.getHeapNode(node).textContent = uiPriv.getStringFromWasm(ptr, len);
 				},
+				__set_document_title(ptr, len) {
+					const title = uiPriv.getStringFromWasm(ptr, len);
+					if(document.title !== title) {
+						document.title = title;
+					}
+				},
 				__set_style(node, pptr, plen, vptr, vlen) {
 					const prop = uiPriv.getStringFromWasm(pptr, plen);
 					const value = uiPriv.getStringFromWasm(vptr, vlen);
 					uiPriv.getHeapNode(node).style[prop] = value;
 				},
+				__set_attribute(node, nptr, nlen, vptr, vlen) {
+					const name = uiPriv.getStringFromWasm(nptr, nlen);
+					const value = uiPriv.getStringFromWasm(vptr, vlen);
+					const element = uiPriv.getHeapNode(node);
+					if(element.getAttribute(name) !== value) {
+						element.setAttribute(name, value);
+					}
+				},
 				__update_event_listener(node, event_ptr, event_len, callback_ptr, component_heapref) {
 					const event = uiPriv.getStringFromWasm(event_ptr, event_len);
 					const key = `__${event}`;
@@ -90,6 +115,34 @@
 					}
 					node.addEventListener(event, node[key]);
 				},
+				__set_scroll_listener(node, listener_ptr, component_heapref) {
+					node = uiPriv.getHeapNode(node);
+					let component = uiPriv.getHeapObject(component_heapref);
+					node.addEventListener('scroll', () => {
+						uiPriv.__dispatch_scroll(listener_ptr, node.scrollLeft, node.scrollTop);
+						component.triggerUpdate();
+					});
+				},
+				__set_scroll_offset(node, x, y) {
+					node = uiPriv.getHeapNode(node);
+					node.scrollLeft = x;
+					node.scrollTop = y;
+				},
+				__client_width(node) {
+					return uiPriv.getHeapNode(node).clientWidth;
+				},
+				__client_height(node) {
+					return uiPriv.getHeapNode(node).clientHeight;
+				},
+				__font_size(node) {
+					return parseFloat(getComputedStyle(uiPriv.getHeapNode(node)).fontSize) || 16;
+				},
+				__viewport_width() {
+					return w.innerWidth;
+				},
+				__viewport_height() {
+					return w.innerHeight;
+				},
 				__heap_object_as_bool(ptr) {
 					const object = uiPriv.getHeapObject(ptr);
 					if(object?.constructor == Boolean) {
@@ -145,38 +198,190 @@
 			},
 		};
 
+		// the css named colors, the same as the compiler's
+		const NAMED_COLORS = new Map(`
+			aliceblue:f0f8ff antiquewhite:faebd7 aqua:00ffff aquamarine:7fffd4 azure:f0ffff
+			beige:f5f5dc bisque:ffe4c4 black:000000 blanchedalmond:ffebcd blue:0000ff
+			blueviolet:8a2be2 brown:a52a2a burlywood:deb887 cadetblue:5f9ea0 chartreuse:7fff00
+			chocolate:d2691e coral:ff7f50 cornflowerblue:6495ed cornsilk:fff8dc crimson:dc143c
+			cyan:00ffff darkblue:00008b darkcyan:008b8b darkgoldenrod:b8860b darkgray:a9a9a9
+			darkgreen:006400 darkgrey:a9a9a9 darkkhaki:bdb76b darkmagenta:8b008b
+			darkolivegreen:556b2f darkorange:ff8c00 darkorchid:9932cc darkred:8b0000
+			darksalmon:e9967a darkseagreen:8fbc8f darkslateblue:483d8b darkslategray:2f4f4f
+			darkslategrey:2f4f4f darkturquoise:00ced1 darkviolet:9400d3 deeppink:ff1493
+			deepskyblue:00bfff dimgray:696969 dimgrey:696969 dodgerblue:1e90ff firebrick:b22222
+			floralwhite:fffaf0 forestgreen:228b22 fuchsia:ff00ff gainsboro:dcdcdc ghostwhite:f8f8ff
+			gold:ffd700 goldenrod:daa520 gray:808080 green:008000 greenyellow:adff2f grey:808080
+			honeydew:f0fff0 hotpink:ff69b4 indianred:cd5c5c indigo:4b0082 ivory:fffff0 khaki:f0e68c
+			lavender:e6e6fa lavenderblush:fff0f5 lawngreen:7cfc00 lemonchiffon:fffacd
+			lightblue:add8e6 lightcoral:f08080 lightcyan:e0ffff lightgoldenrodyellow:fafad2
+			lightgray:d3d3d3 lightgreen:90ee90 lightgrey:d3d3d3 lightpink:ffb6c1 lightsalmon:ffa07a
+			lightseagreen:20b2aa lightskyblue:87cefa lightslategray:778899 lightslategrey:778899
+			lightsteelblue:b0c4de lightyellow:ffffe0 lime:00ff00 limegreen:32cd32 linen:faf0e6
+			magenta:ff00ff maroon:800000 mediumaquamarine:66cdaa mediumblue:0000cd
+			mediumorchid:ba55d3 mediumpurple:9370db mediumseagreen:3cb371 mediumslateblue:7b68ee
+			mediumspringgreen:00fa9a mediumturquoise:48d1cc mediumvioletred:c71585
+			midnightblue:191970 mintcream:f5fffa mistyrose:ffe4e1 moccasin:ffe4b5
+			navajowhite:ffdead navy:000080 oldlace:fdf5e6 olive:808000 olivedrab:6b8e23
+			orange:ffa500 orangered:ff4500 orchid:da70d6 palegoldenrod:eee8aa palegreen:98fb98
+			paleturquoise:afeeee palevioletred:db7093 papayawhip:ffefd5 peachpuff:ffdab9
+			peru:cd853f pink:ffc0cb plum:dda0dd powderblue:b0e0e6 purple:800080
+			rebeccapurple:663399 red:ff0000 rosybrown:bc8f8f royalblue:4169e1 saddlebrown:8b4513
+			salmon:fa8072 sandybrown:f4a460 seagreen:2e8b57 seashell:fff5ee sienna:a0522d
+			silver:c0c0c0 skyblue:87ceeb slateblue:6a5acd slategray:708090 slategrey:708090
+			snow:fffafa springgreen:00ff7f steelblue:4682b4 tan:d2b48c teal:008080 thistle:d8bfd8
+			tomato:ff6347 turquoise:40e0d0 violet:ee82ee wheat:f5deb3 white:ffffff
+			whitesmoke:f5f5f5 yellow:ffff00 yellowgreen:9acd32
+		`.trim().split(/\s+/).map(e => e.split(':')));
+
+		// as in the compiler: `#rgb`, `#rgba`, `#rrggbb`, `#rrggbbaa`, `rgb()`, `rgba()`, `hsl()`,
+		// `hsla()` or a color name, with arguments separated by commas or by spaces and a `/`
+		function parseColor(value) {
+			value = value.trim().toLowerCase();
+			if(value === 'transparent') {
+				return { r: 0, g: 0, b: 0, a: 0 };
+			}
+			let hex = NAMED_COLORS.get(value) ?? value.match(/^#([0-9a-f]{3,4}|[0-9a-f]{6}|[0-9a-f]{8})$/)?.[1];
+			if(hex != null) {
+				if(hex.length <= 4) {
+					hex = hex.replace(/./g, '$&$&');
+				}
+				const [r, g, b, a = 255] = hex.match(/../g).map(e => parseInt(e, 16));
+				return { r, g, b, a: a / 255 };
+			}
+			const [, name, args] = value.match(/^(rgba?|hsla?)\((.*)\)$/) ?? [];
+			const parts = args?.split(/[\s,\/]+/).filter(e => e !== '') ?? [];
+			if(parts.length !== 3 && parts.length !== 4) {
+				return null;
+			}
+			// a plain number, or a percentage of `full`
+			const number = (e, full) => {
+				const [, n, percent] = e.match(/^([+-]?(?:\d*\.\d+|\d+))(%?)$/) ?? [];
+				return n == null ? null : percent ? n / 100 * full : Number(n);
+			};
+			const a = parts.length === 4 ? number(parts[3], 1) : 1;
+			let channels;
+			if(name.startsWith('rgb')) {
+				channels = parts.slice(0, 3).map(e => number(e, 255));
+			} else {
+				const h = number(parts[0].replace(/deg$/, ''), 1);
+				const [s, l] = parts.slice(1, 3).map(e => e.endsWith('%') ? number(e, 1) : null);
+				channels = [h, s, l].includes(null) || parts[0].endsWith('%') ? [null] : hslToRgb(h, s, l);
+			}
+			if([...channels, a].includes(null)) {
+				return null;
+			}
+			const [r, g, b] = channels.map(c => Math.min(Math.max(Math.round(c), 0), 255));
+			return { r, g, b, a: Math.min(Math.max(a, 0), 1) };
+		}
+
+		// `h` in degrees, `s` and `l` as fractions
+		function hslToRgb(h, s, l) {
+			h = ((h % 360) + 360) % 360;
+			s = Math.min(Math.max(s, 0), 1);
+			l = Math.min(Math.max(l, 0), 1);
+			const a = s * Math.min(l, 1 - l);
+			const channel = n => {
+				const k = (n + h / 30) % 12;
+				return (l - a * Math.min(Math.max(Math.min(k - 3, 9 - k), -1), 1)) * 255;
+			};
+			return [channel(0), channel(8), channel(4)];
+		}
+
+		// a css color string (see `parseColor`) or `{ r, g, b, a }` as `{ r, g, b, a }`
+		function sanitizeColor(value) {
+			if(value?.constructor === String) {
+				return parseColor(value);
+			} else if(value instanceof Object) {
+				const [r, g, b] = [value.r, value.g, value.b].map(c => TYPE_SANITIZERS.Int(c));
+				const a = value.a == null ? 1 : TYPE_SANITIZERS.Float(value.a);
+				if([r, g, b, a].includes(null)) {
+					return null;
+				}
+				const byte = c => Math.min(Math.max(c, 0), 255);
+				return { r: byte(r), g: byte(g), b: byte(b), a: Math.min(Math.max(a, 0), 1) };
+			}
+			return null;
+		}
+
+		// a color, or `{ color, position }` with the position as a fraction of the gradient line
+		function sanitizeColorStop(value) {
+			const color = sanitizeColor(value?.color ?? value);
+			if(color == null) {
+				return null;
+			}
+			const position = value?.position == null ? null : TYPE_SANITIZERS.Float(value.position);
+			return position == null ? { color } : { color, position };
+		}
+
 		TYPE_SANITIZERS = {
 			Length(value) {
 				if(value?.constructor === Number) {
 					return `${value}px`;
 				} else if(value?.constructor === String) {
-					const re = /^(\d*\.\d+|\d+)(px|in|cm|mm)$/;
+					const re = /^-?(\d*\.\d+|\d+)(px|in|cm|mm|pt|%|em|vw|vh)$/;
 					if(value.match(re) != null) {
 						return value;
 					}
+					// a sum of terms like `calc(50% - 10px)`
+					const term = '-?(?:\\d*\\.\\d+|\\d+)(?:px|in|cm|mm|pt|%|em|vw|vh)';
+					const calc = new RegExp(`^calc\\(\\s*${term}(?:\\s+[+-]\\s+${term})*\\s*\\)$`);
+					if(value.match(calc) != null) {
+						return value;
+					}
 				}
 				return null;
 			},
+			// a color, `{ type: 'linear', angle, stops }` with the angle in degrees, or
+			// `{ type: 'radial', stops }`
 			Brush(value) {
-				throw new Error("unimplemented!");
+				const color = sanitizeColor(value);
+				if(color != null && value?.type == null) {
+					return { type: 'color', color };
+				}
+				if(value?.type === 'color') {
+					const color = sanitizeColor(value.color);
+					return color == null ? null : { type: 'color', color };
+				}
+				if(!['linear', 'radial'].includes(value?.type) || value.stops?.constructor !== Array) {
+					return null;
+				}
+				const stops = value.stops.map(sanitizeColorStop);
+				if(stops.length < 2 || stops.includes(null)) {
+					return null;
+				}
+				if(value.type === 'radial') {
+					return { type: 'radial', stops };
+				}
+				const angle = TYPE_SANITIZERS.Float(value.angle ?? 180);
+				return angle == null ? null : { type: 'linear', angle, stops };
 			},
 			Alignment(value) {
 				throw new Error("unimplemented!");
 			},
+			Fit(value) {
+				if(['contain', 'cover', 'fill'].includes(value)) {
+					return value;
+				}
+				return null;
+			},
 			Int(value) {
-				if(value?.constructor === Number) {
-					return value|0;
-				} else if(value?.constructor === String) {
-					return parseInt(value)|0;
+				if(value?.constructor === String && value.trim() != '') {
+					value = Number(value);
+				}
+				if(value?.constructor === Number && Number.isFinite(value)) {
+					return Math.trunc(value)|0;
 				}
 				return null;
 			},
 			Float(value) {
-				value = parseFloat(value);
-				if(value == null || Number.isNaN(value)) {
-					return null
+				if(value?.constructor === String && value.trim() != '') {
+					value = Number(value);
+				}
+				if(value?.constructor === Number && !Number.isNaN(value)) {
+					return value;
 				}
-				return value;
+				return null;
 			},
 			String(value) {
 				if(value?.constructor === String) {
@@ -206,6 +411,8 @@
 					getIndex: getComponentExport(name, `${name}__get_index__${key}`, exports),
 					setIndex: getComponentExport(name, `${name}__set_index__${key}`, exports),
 					len:      getComponentExport(name, `${name}__len__${key}`, exports),
+					push:     getComponentExport(name, `${name}__push__${key}`, exports),
+					splice:   getComponentExport(name, `${name}__splice__${key}`, exports),
 				};
 				this.key = key;
 				this.baseType = baseType;
@@ -225,6 +432,38 @@
 				this.component.triggerUpdate();
 				return true;
 			}
+			push(...values) {
+				for(let value of values) {
+					value = uiPriv.sanitize(value, this.baseType, this.component);
+					if(value != null) {
+						this.methods.push(this.component.ptr, uiPriv.addToHeap(value));
+					}
+				}
+				this.component.triggerUpdate();
+				return this.length;
+			}
+			// follows `Array.prototype.splice`, including negative and missing arguments
+			splice(start, deleteCount, ...items) {
+				const length = this.length;
+				start = Math.trunc(start) || 0;
+				start = start < 0 ? Math.max(length + start, 0) : Math.min(start, length);
+				deleteCount = deleteCount === undefined ? length - start : Math.max(Math.trunc(deleteCount) || 0, 0);
+				items = items
+					.map(value => uiPriv.sanitize(value, this.baseType, this.component))
+					.filter(value => value != null);
+				const removed = this.methods.splice(this.component.ptr, start, deleteCount, uiPriv.addToHeap(items));
+				this.component.triggerUpdate();
+				return uiPriv.dropFromHeap(removed);
+			}
+			insert(index, value) {
+				this.splice(index, 0, value);
+			}
+			remove(index) {
+				return this.splice(index, 1)[0];
+			}
+			clear() {
+				this.splice(0, this.length);
+			}
 			toJSON() {
 				return uiPriv.dropFromHeap(this.methods.getter(this.component.ptr));
 			}
@@ -324,7 +563,20 @@
 						return null;
 					}
 				} else if(type instanceof Object) {
-					throw new Error('unimplemented!');
+					if(!(value instanceof Object) || value instanceof Array) {
+						return null;
+					}
+					let sanitized = {};
+					for(let key in type) {
+						if(value[key] == null) {
+							continue;
+						}
+						let result = this.sanitize(value[key], type[key], ctx);
+						if(result != null) {
+							sanitized[key] = result;
+						}
+					}
+					return sanitized;
 				}
 				return TYPE_SANITIZERS[type]?.(value, ctx);
 			},
@@ -405,7 +657,7 @@
 					for(key in propsDef) {
 						if(propsDef[key] == 'Callback') {
 							continue;
-						} else if(this[key] instanceof Object) {
+						} else if(this[key]?.toJSON instanceof Function) {
 							result[key] = this[key].toJSON();
 						} else {
 							result[key] = this[key];
@@ -423,6 +675,8 @@
 					}
 					let eptr = uiPriv.addToHeap(element);
 					componentPriv.__attach_to_element(this.ptr, eptr);
+					// relative lengths are resolved to pixels, so they are redone when the window resizes
+					w.addEventListener('resize', () => this.triggerUpdate());
 					this.render();
 				};
 				Class.prototype.render = function() {
@@ -477,5 +731,6 @@
 		uiPriv.wasm.then(wasm => {
 			uiPriv.memory = wasm.instance.exports.memory;
 			uiPriv.__dispatch_bound_callback = wasm.instance.exports.__dispatch_bound_callback;
+			uiPriv.__dispatch_scroll = wasm.instance.exports.__dispatch_scroll;
 		});
 	})
\ No newline at end of file
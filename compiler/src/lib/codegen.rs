@@ -1,53 +1,207 @@
-use std::path::PathBuf;
-use std::fs::File;
-use std::io::Write as IoWrite;
+use std::path::{Path, PathBuf};
 use std::fmt::Write as FmtWrite;
 use std::collections::HashMap;
 use quote::{quote, format_ident};
 use convert_case::{Case, Casing};
-use proc_macro2::TokenStream;
+use proc_macro2::{TokenStream, Ident};
 
 use super::{
 	Value,
+	Collection,
+	Gradient,
+	ColorStop,
 	Type,
 	Ctx,
 	Expr,
+	UnaryOp,
+	BinaryOp,
 	PropDecl,
+	diagnostic::{Diagnostic, Span as SourceSpan},
 	elements::{
 		Empty,
 		Rect,
 		Scroll,
 		Span,
 		Text,
+		Img,
+		Window,
 		ComponentInstance,
 		Layout,
 		ElementData,
 		Component,
 		Element,
 		Content,
+		Children,
+		AddedProperties,
+		prop_deps,
 	}
 };
 
+type CodeGenResult = Result<TokenStream, String>;
+
 fn codegen_element(e: &Element, ctx: &mut CodeGenCtx) -> TokenStream {
-	let parent = CodeGen::generate(e.element_impl.as_ref(), e.data(), ctx);
+	let index = ctx.index;
+	let tokens = if e.otherwise.is_some() {
+		codegen_chain(e, ctx)
+	} else {
+		let result = e.condition.as_ref()
+			.map(|cond| cond.to_tokens())
+			.transpose()
+			.and_then(|cond| codegen_conditional(e, cond, ctx));
+		match result {
+			Ok(tokens) => tokens,
+			Err(message) => {
+				ctx.error(e.span, message);
+				return quote!();
+			},
+		}
+	};
+
+	// a subtree none of whose props changed is restored as it was last built; inside a repeater
+	// the loop variables may have changed too, so there it's whole rows that are restored
+	let mut deps = Vec::new();
+	if !ctx.loop_vars.is_empty() || !subtree_deps(e, &mut deps) {
+		return tokens;
+	}
+	match ctx.mask(deps) {
+		0 => quote!(
+			if !parent.restore_in(#index) {
+				#tokens
+			}
+		),
+		mask => quote!(
+			if changes & #mask != 0 || !parent.restore_in(#index) {
+				#tokens
+			}
+		),
+	}
+}
+
+// collects the props read anywhere below `e`; false if the subtree holds a nested component or
+// a slot, which are built from state other than ours
+fn subtree_deps<'a>(e: &'a Element, deps: &mut Vec<&'a String>) -> bool {
+	for branch in e.branches() {
+		deps.extend(branch.repeater.iter().flat_map(|e| e.deps.iter()));
+		if !row_deps(branch, deps) {
+			return false;
+		}
+	}
+	true
+}
+
+// like `subtree_deps`, but for a single element built from `e`, e.g. one row of its repeater
+fn row_deps<'a>(e: &'a Element, deps: &mut Vec<&'a String>) -> bool {
+	if e.element_impl.projects_children() {
+		return false;
+	}
+	deps.extend(e.deps.iter());
+	e.children.iter().all(|child| match child {
+		Content::Element(child) => subtree_deps(child, deps),
+		Content::Children(_) => false,
+	})
+}
+
+// the element is only shown while `condition` holds
+fn codegen_conditional(e: &Element, condition: Option<TokenStream>, ctx: &mut CodeGenCtx) -> CodeGenResult {
+	let outer_vars = ctx.loop_vars.len();
+	if let Some(repeater) = &e.repeater {
+		ctx.loop_vars.push(repeater.item.clone());
+		ctx.loop_vars.extend(repeater.index.iter().cloned());
+	}
+	let result = codegen_element_impl(e, condition, ctx);
+	ctx.loop_vars.truncate(outer_vars);
+	result
+}
 
+// an `if`/`else` chain is a group with a slot for each branch, so that each branch keeps its own
+// elements; the one whose condition holds first is shown and the others are hidden
+fn codegen_chain(e: &Element, ctx: &mut CodeGenCtx) -> TokenStream {
 	let index = ctx.index;
+	let branches: Vec<_> = e.branches().collect();
 
-	let mut children = Vec::new();
-	for (i, child)in e.children.iter().enumerate() {
+	// the index of the branch to show, or one past the last if none is
+	let mut shown = {
+		let none = branches.len();
+		quote!(#none)
+	};
+	for (i, branch) in branches.iter().enumerate().rev() {
+		shown = match &branch.condition {
+			Some(cond) => match cond.to_tokens() {
+				Ok(cond) => quote!(if #cond { #i } else { #shown }),
+				Err(message) => {
+					ctx.error(branch.span, message);
+					return quote!();
+				},
+			},
+			None => quote!(#i),
+		};
+	}
+
+	let mut rendered = Vec::new();
+	for (i, branch) in branches.iter().enumerate() {
 		ctx.index = i;
-		match child {
-			Content::Element(child) => children.push(codegen_element(child, ctx)),
-			_ => unimplemented!()
+		match codegen_conditional(branch, Some(quote!(shown == #i)), ctx) {
+			Ok(tokens) => rendered.push(tokens),
+			Err(message) => ctx.error(branch.span, message),
+		}
+	}
+	ctx.index = index;
+
+	quote!(
+		{
+			let shown: usize = #shown;
+			let parent = parent.begin_group(#index);
+			#(#rendered;)*
+		}
+	)
+}
+
+// renders the elements passed in by whoever instantiated this component
+fn codegen_slot(c: &Children, index: usize) -> TokenStream {
+	let filter = c.filter.iter().flatten().map(|e| e.as_str());
+	quote!(
+		{
+			let e = parent.begin_group(#index);
+			slot.render(e, &[#(#filter),*]);
+		}
+	)
+}
+
+fn codegen_element_impl(e: &Element, condition: Option<TokenStream>, ctx: &mut CodeGenCtx) -> CodeGenResult {
+	let index = ctx.index;
+
+	let parent = CodeGen::generate(e.element_impl.as_ref(), e.data(), ctx)?;
+	let update = CodeGen::generate_in(e.element_impl.as_ref(), e.data(), ctx)?;
+
+	let mut children = Vec::new();
+	if !e.element_impl.projects_children() {
+		for (i, child) in e.children.iter().enumerate() {
+			ctx.index = i;
+			match child {
+				Content::Element(child) => children.push(codegen_element(child, ctx)),
+				Content::Children(c) => children.push(codegen_slot(c, i)),
+			}
 		}
 	}
 
+	// how the element is placed by the row or column it is in
+	let layout_item = if let AddedProperties::Layout(item) = &e.added_properties {
+		let align = item.align.to_tokens()?;
+		let stretch = match &item.stretch {
+			Value::Unset => quote!(1.0),
+			v => v.to_tokens()?,
+		};
+		quote!(e.layout_item = Some(ui::LayoutItem { align: #align, stretch: #stretch });)
+	} else {
+		quote!()
+	};
+
 	let mut events = Vec::new();
 
 	macro_rules! gen_event {
 		($event:ident) => {
 			if e.events.$event.is_set() {
-				let callback = e.events.$event.to_tokens();
+				let callback = e.events.$event.to_tokens()?;
 				let event_type = format_ident!("{}", stringify!($event).to_case(Case::UpperCamel));
 				events.push(quote!(
 					e.handle_event(this.clone(), ui::EventType::#event_type, Some(#callback));
@@ -62,26 +216,88 @@ fn codegen_element(e: &Element, ctx: &mut CodeGenCtx) -> TokenStream {
 	gen_event!(pointer_move);
 	gen_event!(pointer_in);
 	gen_event!(pointer_out);
+	gen_event!(scroll);
+
+	let tokens = if let Some(repeater) = &e.repeater {
+		let collection = repeater.collection.to_tokens_iter()?;
+		let (key, element_in, restore_in, end_group) = match &repeater.key {
+			Some(Value::Binding(key, _)) => {
+				let key = key.to_tokens()?;
+				(
+					quote!(let key = ui::Key::from(#key);),
+					quote!(parent.keyed_element_in(e_impl, i, key)),
+					quote!(parent.restore_keyed_in(i, &key)),
+					quote!(end_keyed_group),
+				)
+			},
+			_ => (quote!(), quote!(parent.element_in(e_impl, i)), quote!(parent.restore_in(i)), quote!(end_group)),
+		};
+		let item = loop_var(&repeater.item);
+		let index_var = repeater.index.as_ref().map(|e| {
+			let var = loop_var(e);
+			quote!(let #var = i as i32;)
+		});
+		let row = quote!(
+			#parent
+			let e = #element_in;
+			#layout_item
+			#(#events)*
+			#update
+			#(
+				let e = {
+					let parent = e;
+					#children
+					parent
+				};
+			)*
+		);
+
+		// outside of other repeaters, a row is restored unless its item or the props it reads
+		// changed; the rows of a list prop that changed are known from its edits, those of anything
+		// else only from whether what it reads changed
+		let own_vars = 1 + repeater.index.iter().count();
+		let mut deps = Vec::new();
+		let (rows, row) = if ctx.loop_vars.len() == own_vars && row_deps(e, &mut deps) {
+			let list = match &repeater.collection {
+				Collection::Value(Value::Binding(Expr::Path(path, Ctx::Component), _)) if path.len() == 1 => Some(&path[0]),
+				_ => None,
+			};
+			let (rows, changed) = match list {
+				Some(list) => {
+					let edits = edits_var(list);
+					let field = format_ident!("{}", list);
+					// the elements of a keyed repeater stay with their items, unless they show the index
+					let keyed = repeater.key.is_some() && repeater.index.is_none();
+					let rows = quote!(let rows = ui::changed_rows(&#edits, this.borrow().#field.len(), #keyed););
+					match ctx.mask(deps) {
+						0 => (rows, quote!(rows[i])),
+						mask => (rows, quote!(rows[i] || changes & #mask != 0)),
+					}
+				},
+				None => match ctx.mask(deps.into_iter().chain(repeater.deps.iter())) {
+					0 => (quote!(), quote!(false)),
+					mask => (quote!(), quote!(changes & #mask != 0)),
+				},
+			};
+			(rows, quote!(
+				if #changed || !#restore_in {
+					#row
+				}
+			))
+		} else {
+			(quote!(), row)
+		};
 
-	if let Some(repeater) = &e.repeater {
-		let collection = repeater.collection.to_tokens_iter();
 		let group = quote!(
-			for item in #collection {
-				#parent
-				let e = parent.element_in(e_impl, i);
-				#(#events)*
-				#(
-					let e = {
-						let parent = e;
-						#children
-						parent
-					};
-				)*
+			#rows
+			for #item in #collection {
+				#index_var
+				#key
+				#row
 				i += 1;
 			}
 		);
-		if let Some(cond) = &e.condition {
-			let cond = cond.to_tokens();
+		if let Some(cond) = condition {
 			quote!(
 				{
 					let parent = parent.begin_group(#index);
@@ -89,7 +305,7 @@ fn codegen_element(e: &Element, ctx: &mut CodeGenCtx) -> TokenStream {
 					if #cond {
 						#group
 					}
-					parent.end_group(i);
+					parent.#end_group(i);
 				}
 			)
 		} else {
@@ -98,14 +314,34 @@ fn codegen_element(e: &Element, ctx: &mut CodeGenCtx) -> TokenStream {
 					let parent = parent.begin_group(#index);
 					let mut i = 0;
 					#group
-					parent.end_group(i);
+					parent.#end_group(i);
 				}
 			)
 		}
 	} else {
+		// outside of repeaters, an element whose own props didn't change is restored
+		let element_in = if ctx.loop_vars.is_empty() {
+			let changed = match ctx.mask(&e.deps) {
+				0 => quote!(false),
+				mask => quote!(changes & #mask != 0),
+			};
+			quote!(parent.element_in_with(#index, #changed, || {
+				#parent
+				e_impl
+			}))
+		} else {
+			quote!(
+				{
+					#parent
+					parent.element_in(e_impl, #index)
+				}
+			)
+		};
 		let body = quote!(
-			let e = parent.element_in(e_impl, #index);
+			let e = #element_in;
+			#layout_item
 			#(#events)*
+			#update
 			#(
 				let e = {
 					let parent = e;
@@ -114,51 +350,87 @@ fn codegen_element(e: &Element, ctx: &mut CodeGenCtx) -> TokenStream {
 				};
 			)*
 		);
-		if let Some(cond) = &e.condition {
-			let cond = cond.to_tokens();
+		if let Some(cond) = condition {
 			quote!(
-				#parent
 				if #cond {
 					#body
 				} else {
+					#parent
 					parent.element_out(e_impl, #index);
 				}
 			)
 		} else {
-			quote!(
-				#parent
-				#body
-			)
+			body
 		}
-	}
+	};
+	Ok(tokens)
 }
 
+// `imports` lists every component the root component transitively depends on, each of which is
+// compiled into a module of its own so that its `Props` and `Callback` don't clash with ours.
+// Those modules are all declared next to each other by the root, so imported components get
+// `None` and reach their own imports through `super`
 pub fn generate<S1: Into<String>, S2: Into<String>, P: Into<PathBuf>>(
 	component: &Component,
+	imports: Option<&[(String, PathBuf)]>,
 	_script: Option<S1>,
 	name: S2,
 	path: P,
 	web: bool,
-) {
+) -> Result<String, Vec<Diagnostic>> {
 	let name = name.into();
+	let path = path.into();
+
+	let out_dir = dist_dir(&path);
+	let import_modules = imports.unwrap_or_default().iter().map(|(name, import_path)| {
+		let module = format_ident!("{}", name.to_case(Case::Snake));
+		let file = relative_path(&out_dir, &dist_dir(import_path).join(format!("{}.rs", name)));
+		let file = file.to_string_lossy().replace('\\', "/");
+		quote!(
+			pub mod #module {
+				include!(#file);
+			}
+		)
+	});
 	let struct_name = format_ident!("{}", name.clone().to_case(Case::UpperCamel));
 
 	let mut ctx = CodeGenCtx::new(name, path);
+	ctx.is_root = imports.is_some();
+	ctx.props = sorted_props(&component.props);
 	let code = codegen_element(&component.root, &mut ctx);
 
+	let mut fields = Vec::new();
+	let mut prop_types = HashMap::new();
 	let mut pub_fields = Vec::new();
 	let mut pub_field_inits = Vec::new();
-	let mut priv_fields = Vec::new();
 	let mut priv_field_inits = Vec::new();
+	let mut pub_field_defaults = Vec::new();
+	let mut prop_structs = Vec::new();
 	for (name, decl) in component.props.iter() {
+		let type_name = format!("{}{}", struct_name, name.to_case(Case::UpperCamel));
 		let name = format_ident!("{}", name);
-		let prop_type = decl.prop_type.to_tokens();
+		let prop_type = match gen_prop_type(&type_name, &decl.prop_type, web, &mut prop_structs) {
+			Ok(prop_type) => prop_type,
+			Err(message) => {
+				ctx.error(decl.span, message);
+				continue;
+			},
+		};
+		let default = match gen_prop_default(decl) {
+			Ok(default) => default,
+			Err(message) => {
+				ctx.error(decl.span, message);
+				continue;
+			},
+		};
+		fields.push(quote!(#name: #prop_type,));
+		prop_types.insert(name.to_string(), prop_type.clone());
 		if decl.is_pub {
 			pub_fields.push(quote!(pub #name: #prop_type,));
 			pub_field_inits.push(quote!(#name: props.#name,));
+			pub_field_defaults.push(quote!(#name: #default,));
 		} else {
-			priv_fields.push(quote!(#name: #prop_type,));
-			priv_field_inits.push(quote!(#name: Default::default(),));
+			priv_field_inits.push(quote!(#name: #default,));
 		}
 	}
 
@@ -171,12 +443,13 @@ pub fn generate<S1: Into<String>, S2: Into<String>, P: Into<PathBuf>>(
 		let new_component = format_ident!("{}__new_component", struct_name);
 		let drop_component = format_ident!("{}__drop_component", struct_name);
 		let get_props_json = format_ident!("{}__get_props_json", struct_name);
-		let props_json = gen_props_json(&component.props);
+		let props_json = gen_props_json(&component.props, &mut ctx);
 		let mut js_field_inits = Vec::new();
 		let mut props = Vec::new();
 		for (name, decl) in component.props.iter().filter(|(_, decl)| decl.is_pub) {
 			let name_ident = format_ident!("{}", name);
 			let setter_name = format_ident!("{}__set__{}", struct_name, name);
+			let set = format_ident!("set_{}", name);
 
 			let getter = match decl.prop_type {
 				Type::Callback => {
@@ -214,7 +487,7 @@ pub fn generate<S1: Into<String>, S2: Into<String>, P: Into<PathBuf>>(
 				#[allow(non_snake_case)]
 				pub fn #setter_name(this: #abi_struct_name, value: ui::JsValue) {
 					let interface = #interface_struct_name::from_abi(this);
-					interface.component.borrow_mut().#name_ident = ui::FromJsValue::from_js_value(value);
+					interface.component.borrow_mut().#set(ui::FromJsValue::from_js_value(value));
 					interface.release_into_js();
 				}
 			));
@@ -224,6 +497,8 @@ pub fn generate<S1: Into<String>, S2: Into<String>, P: Into<PathBuf>>(
 					let get_index = format_ident!("{}__get_index__{}", struct_name, name);
 					let set_index = format_ident!("{}__set_index__{}", struct_name, name);
 					let len = format_ident!("{}__len__{}", struct_name, name);
+					let push = format_ident!("{}__push__{}", struct_name, name);
+					let splice = format_ident!("{}__splice__{}", struct_name, name);
 					props.push(quote!(
 						#[no_mangle]
 						#[allow(non_snake_case)]
@@ -248,6 +523,24 @@ pub fn generate<S1: Into<String>, S2: Into<String>, P: Into<PathBuf>>(
 							interface.release_into_js();
 							result
 						}
+						#[no_mangle]
+						#[allow(non_snake_case)]
+						pub fn #push(this: #abi_struct_name, value: ui::JsValue) {
+							let interface = #interface_struct_name::from_abi(this);
+							interface.component.borrow_mut().#name_ident.push(ui::FromJsValue::from_js_value(value));
+							interface.release_into_js();
+						}
+						#[no_mangle]
+						#[allow(non_snake_case)]
+						pub fn #splice(this: #abi_struct_name, index: usize, removed: usize, items: ui::JsValue) -> ui::JsValue {
+							let interface = #interface_struct_name::from_abi(this);
+							let items: Vec<_> = items.into_iter()
+								.map(|iter| iter.map(ui::FromJsValue::from_js_value).collect())
+								.unwrap_or_default();
+							let removed = interface.component.borrow_mut().#name_ident.splice(index, removed, items);
+							interface.release_into_js();
+							ui::AsJsValue::as_js_value(removed.as_slice())
+						}
 					));
 				},
 				_ => {},
@@ -344,77 +637,255 @@ pub fn generate<S1: Into<String>, S2: Into<String>, P: Into<PathBuf>>(
 		quote!()
 	};
 
+	// the edits to each list since the last update, which also count as changes to it
+	let lists: Vec<_> = ctx.props.iter()
+		.filter(|name| matches!(component.props[*name].prop_type, Type::Iter(_)))
+		.collect();
+	let list_fields = lists.iter().map(|name| format_ident!("{}", name));
+	let list_edits: Vec<_> = lists.iter().map(|name| edits_var(name)).collect();
+	let list_bits = lists.iter().map(|name| ctx.mask([*name]));
+
+	// the fields are private so that nothing outside the component's module can write one
+	// without marking it; a method per prop rather than one taking its name, so that a
+	// misspelled prop doesn't compile
+	let mut accessors = Vec::new();
+	for name in ctx.props.iter() {
+		let Some(prop_type) = prop_types.get(name) else {
+			continue;
+		};
+		let bit = ctx.mask([name]);
+		let vis = if component.props[name].is_pub { quote!(pub) } else { quote!() };
+		let field = format_ident!("{}", name);
+		let set = format_ident!("set_{}", name);
+		let field_mut = format_ident!("{}_mut", name);
+		accessors.push(quote!(
+			#[allow(dead_code)]
+			#vis fn #set(&mut self, value: #prop_type) {
+				self.#field = value;
+				self.__dirty.mark(#bit);
+			}
+			#[allow(dead_code)]
+			#vis fn #field_mut(&mut self) -> &mut #prop_type {
+				self.__dirty.mark(#bit);
+				&mut self.#field
+			}
+		));
+	}
+
 	let code = quote!(
 		pub type Callback = ui::Callback<#struct_name>;
+		#(#import_modules)*
+		#(#prop_structs)*
 		
-		#[derive(Default, Debug)]
+		#[doc = "Props are written with their `set_<prop>` method or changed in place through `<prop>_mut`, both of which mark them for the next update to show."]
+		#[derive(Debug)]
 		#[allow(dead_code)]
 		pub struct #struct_name {
-			#(#pub_fields)*
-			#(#priv_fields)*
+			#(#fields)*
+			__dirty: ui::Dirty,
 		}
-		#[derive(Default, Debug)]
+		impl Default for #struct_name {
+			fn default() -> Self {
+				Self {
+					#(#pub_field_defaults)*
+					#(#priv_field_inits)*
+					__dirty: Default::default(),
+				}
+			}
+		}
+		#[derive(Debug)]
 		pub struct Props {
 			#(#pub_fields)*
 		}
+		impl Default for Props {
+			fn default() -> Self {
+				Self {
+					#(#pub_field_defaults)*
+				}
+			}
+		}
 		impl #struct_name {
 			pub fn new(props: Props) -> Self {
 				let mut instance = Self {
 					#(#pub_field_inits)*
 					#(#priv_field_inits)*
+					__dirty: Default::default(),
 				};
 				ui::Component::on_init(&mut instance);
 				instance
 			}
+			#(#accessors)*
 		}
 		impl ui::ComponentBase for #struct_name {
 			type Abi = ui::Abi;
 
-			#[allow(unused_variables)]
+			#[allow(unused_variables, clippy::let_and_return)]
 			fn update<D: ui::ElementData>(this: std::rc::Rc<std::cell::RefCell<#struct_name>>, parent: &mut ui::GenericElement<D>) {
+				let slot = parent.slot();
 				ui::Component::on_update(&mut *this.borrow_mut());
+				#(let #list_edits = this.borrow_mut().#list_fields.take_changes();)*
+				let changes = this.borrow().__dirty.take() #(| if #list_edits.is_empty() { 0 } else { #list_bits })*;
 				#code
 			}
+			fn dirty(&self) -> &ui::Dirty {
+				&self.__dirty
+			}
 		}
 		#web_code
 	);
 
-	writeln!(ctx.file, "{code}").unwrap();
+	if ctx.diagnostics.is_empty() {
+		Ok(code.to_string())
+	} else {
+		Err(ctx.diagnostics)
+	}
+}
+
+pub fn dist_dir(source_path: &Path) -> PathBuf {
+	let mut dir = source_path.parent().unwrap_or(Path::new("")).to_path_buf();
+	dir.push("dist");
+	dir
+}
+
+fn relative_path(from_dir: &Path, to: &Path) -> PathBuf {
+	let from: Vec<_> = from_dir.components().collect();
+	let to: Vec<_> = to.components().collect();
+	let common = from.iter().zip(to.iter()).take_while(|(a, b)| a == b).count();
+	let mut result = PathBuf::new();
+	for _ in common..from.len() {
+		result.push("..");
+	}
+	for component in &to[common..] {
+		result.push(component);
+	}
+	result
+}
+
+// writes through a temporary file so that a failed build never leaves a truncated file behind
+pub fn write(dir: &Path, name: &str, code: &str) -> std::io::Result<()> {
+	std::fs::create_dir_all(dir)?;
+	let timestamp = std::time::SystemTime::now()
+		.duration_since(std::time::UNIX_EPOCH)
+		.map(|e| e.as_millis())
+		.unwrap_or_default();
+	let tempname = dir.join(format!("{}.rs.{}", name, timestamp));
+	std::fs::write(&tempname, format!("{code}\n"))?;
+
+	let path = dir.join(format!("{}.rs", name));
+	if path.is_file() {
+		std::fs::remove_file(&path)?;
+	}
+	std::fs::rename(&tempname, path)
+}
+
+fn gen_prop_default(decl: &PropDecl) -> CodeGenResult {
+	match &decl.default {
+		Some(value) => value.to_tokens_move(),
+		None => Ok(quote!(Default::default())),
+	}
+}
+
+// object types have no name of their own, so each one becomes a struct named after the
+// component and the property that declares it, e.g. `TodoListTodosItem` for `todos: [{ … }]`
+fn gen_prop_type(name: &str, prop_type: &Type, web: bool, structs: &mut Vec<TokenStream>) -> CodeGenResult {
+	match prop_type {
+		Type::Object(map) => {
+			let ident = format_ident!("{}", name);
+			let mut fields: Vec<_> = map.iter().collect();
+			fields.sort_by_key(|(field, _)| field.as_str());
 
-	ctx.finalize();
+			let mut field_defs = Vec::new();
+			for (field, field_type) in fields.iter() {
+				if **field_type == Type::Callback {
+					return Err(format!("callbacks cannot be fields of an object (found `{}`)", field));
+				}
+				let field_name = format!("{}{}", name, field.to_case(Case::UpperCamel));
+				let field_type = gen_prop_type(&field_name, field_type, web, structs)?;
+				let field = format_ident!("{}", field);
+				field_defs.push(quote!(pub #field: #field_type,));
+			}
+			structs.push(quote!(
+				#[derive(Default, Debug, Clone)]
+				pub struct #ident {
+					#(#field_defs)*
+				}
+			));
+
+			if web {
+				let keys: Vec<_> = fields.iter().map(|(field, _)| field.as_str()).collect();
+				let idents: Vec<_> = keys.iter().map(|field| format_ident!("{}", field)).collect();
+				structs.push(quote!(
+					#[cfg(target_arch = "wasm32")]
+					impl ui::AsJsValue for #ident {
+						fn as_js_value(&self) -> ui::JsValue {
+							let object = ui::JsValue::new_object();
+							#(object.set_property(#keys, ui::AsJsValue::as_js_value(&self.#idents));)*
+							object
+						}
+					}
+					#[cfg(target_arch = "wasm32")]
+					impl ui::FromJsValue for #ident {
+						fn from_js_value(value: ui::JsValue) -> Self {
+							Self {
+								#(#idents: value.get_property(#keys)
+									.map(|e| ui::FromJsValue::from_js_value(e))
+									.unwrap_or_default(),)*
+							}
+						}
+					}
+				));
+			}
+			Ok(quote!(#ident))
+		},
+		Type::Iter(t) => {
+			let t = gen_prop_type(&format!("{}Item", name), t, web, structs)?;
+			Ok(quote!(ui::Iterable<#t>))
+		},
+		_ => prop_type.to_tokens(),
+	}
 }
 
-fn gen_props_json(props: &HashMap<String, PropDecl>) -> String {
+fn gen_props_json(props: &HashMap<String, PropDecl>, ctx: &mut CodeGenCtx) -> String {
 	let mut buf = String::new();
 	write!(buf, "{{").unwrap();
-	let mut it = props.iter().filter(|(_, e)| e.is_pub);
-	if let Some((_, decl)) = it.next() {
-		write!(buf, "\"{}\":", decl.name).unwrap();
-		gen_type_json(&mut buf, &decl.prop_type);
-	}
-	for (_, decl) in it {
-		write!(buf, ",\"{}\":", decl.name).unwrap();
-		gen_type_json(&mut buf, &decl.prop_type);
+	let mut first = true;
+	for (_, decl) in props.iter().filter(|(_, e)| e.is_pub) {
+		let mut type_json = String::new();
+		if let Err(message) = gen_type_json(&mut type_json, &decl.prop_type) {
+			ctx.error(decl.span, message);
+			continue;
+		}
+		if !first {
+			write!(buf, ",").unwrap();
+		}
+		write!(buf, "\"{}\":{}", decl.name, type_json).unwrap();
+		first = false;
 	}
 	write!(buf, "}}").unwrap();
 	buf
 }
 
-fn gen_type_json(buf: &mut String, prop_type: &Type) {
+fn gen_type_json(buf: &mut String, prop_type: &Type) -> Result<(), String> {
 	match prop_type {
 		Type::Object(map) => {
 			write!(buf, "{{").unwrap();
 			let mut it = map.iter();
 			if let Some((name, prop_type)) = it.next() {
 				write!(buf, "\"{}\":", name).unwrap();
-				gen_type_json(buf, prop_type);
+				gen_type_json(buf, prop_type)?;
 			}
 			for (name, prop_type) in it {
 				write!(buf, ",\"{}\":", name).unwrap();
-				gen_type_json(buf, prop_type);
+				gen_type_json(buf, prop_type)?;
 			}
 			write!(buf, "}}").unwrap();
 		}
+		Type::Int => {
+			write!(buf, "\"Int\"").unwrap();
+		}
+		Type::Float => {
+			write!(buf, "\"Float\"").unwrap();
+		}
 		Type::Length => {
 			write!(buf, "\"Length\"").unwrap();
 		}
@@ -424,6 +895,9 @@ fn gen_type_json(buf: &mut String, prop_type: &Type) {
 		Type::Alignment => {
 			write!(buf, "\"Alignment\"").unwrap();
 		}
+		Type::Fit => {
+			write!(buf, "\"Fit\"").unwrap();
+		}
 		Type::String => {
 			write!(buf, "\"String\"").unwrap();
 		}
@@ -432,70 +906,62 @@ fn gen_type_json(buf: &mut String, prop_type: &Type) {
 		}
 		Type::Iter(t) => {
 			write!(buf, "[").unwrap();
-			gen_type_json(buf, t);
+			gen_type_json(buf, t)?;
 			write!(buf, "]").unwrap();
 		}
 		Type::Callback => {
 			write!(buf, "\"Callback\"").unwrap();
 		}
 		t => {
-			unimplemented!("rendering data type: {:?}", t);
+			return Err(format!("public properties of type {:?} cannot be exposed to JavaScript yet", t));
 		}
 	}
+	Ok(())
 }
 
 pub struct CodeGenCtx {
-	file: File,
 	name: String,
-	dir: PathBuf,
-	tempname: PathBuf,
+	path: PathBuf,
 	index: usize,
+	// the loop variables in scope for the element being generated, outermost first
+	loop_vars: Vec<String>,
+	// the names of the component's props, sorted, whose positions are their bits in `ui::Dirty`
+	props: Vec<String>,
+	is_root: bool,
+	diagnostics: Vec<Diagnostic>,
 }
 
 impl CodeGenCtx {
-	pub fn new<S: Into<String>, P: Into<PathBuf>>(name: S, dir: P) -> CodeGenCtx {
-		let name = name.into();
-		let dir = dir.into();
-		std::fs::create_dir_all(&dir).unwrap();
-		let mut tempname = dir.clone();
-		let timestamp = std::time::SystemTime::now()
-			.duration_since(std::time::UNIX_EPOCH)
-			.unwrap()
-			.as_millis();
-		tempname.push(format!("{}.rs.{}", name, timestamp));
-		let file = File::create(&tempname).unwrap();
+	pub fn new<S: Into<String>, P: Into<PathBuf>>(name: S, path: P) -> CodeGenCtx {
 		CodeGenCtx {
-			file,
-			name,
+			name: name.into(),
+			path: path.into(),
 			index: 0,
-			dir,
-			tempname,
+			loop_vars: Vec::new(),
+			props: Vec::new(),
+			is_root: true,
+			diagnostics: Vec::new(),
 		}
 	}
 
-	fn finalize(self) {
-		std::mem::drop(self.file);
-		let mut path = self.dir;
-		path.push(format!("{}.rs", self.name));
-		if path.is_file() {
-			if std::fs::remove_file(&path).is_err() {
-				eprintln!("unable to replace file: {}", path.display());
-				return;
-			}
-		}
-		if std::fs::rename(&self.tempname, path).is_err() {
-			eprintln!("unable to rename file: {}", self.tempname.display());
-			return;
-		}
+	fn mask<'a, I: IntoIterator<Item = &'a String>>(&self, deps: I) -> u64 {
+		prop_mask(&self.props, deps)
+	}
+
+	fn error<S: Into<String>>(&mut self, span: SourceSpan, message: S) {
+		self.diagnostics.push(Diagnostic::error(self.path.clone(), span, message));
 	}
 }
 
 impl Type {
-	fn to_tokens(&self) -> TokenStream {
-		match self {
+	fn to_tokens(&self) -> CodeGenResult {
+		let tokens = match self {
 			Type::Int => {
 				quote!(i32)
 			},
+			Type::Float => {
+				quote!(f32)
+			},
 			Type::Length => {
 				quote!(ui::Length)
 			},
@@ -511,37 +977,67 @@ impl Type {
 			Type::Alignment => {
 				quote!(ui::Alignment)
 			},
+			Type::Fit => {
+				quote!(ui::Fit)
+			},
 			Type::Callback => {
 				quote!(Callback)
 			},
 			Type::Iter(t) => {
-				let t = t.to_tokens();
+				let t = t.to_tokens()?;
 				quote!(ui::Iterable<#t>)
 			},
-			_ => unimplemented!("render values as tokens unimplemented for {:?}", self)
-		}
+			Type::Object(_) => {
+				return Err(String::from("object types are only supported in property declarations"));
+			},
+			Type::Any => {
+				return Err(String::from("cannot infer a concrete type for this value"));
+			},
+		};
+		Ok(tokens)
 	}
 }
 
+fn color_stop_tokens(stop: &ColorStop) -> TokenStream {
+	let (r, g, b, a) = stop.color;
+	let position = match stop.position {
+		Some(position) => quote!(Some(#position)),
+		None => quote!(None),
+	};
+	quote!(ui::ColorStop { color: ui::Color { r: #r, g: #g, b: #b, a: #a }, position: #position })
+}
+
 impl Value {
-	fn to_tokens_optional(&self) -> TokenStream {
+	fn to_tokens_optional(&self) -> CodeGenResult {
 		match self {
 			Value::Unset => {
-				quote!(None)
+				Ok(quote!(None))
 			},
 			_ => {
-				let res = self.to_tokens();
-				quote!(Some(#res))
+				let res = self.to_tokens()?;
+				Ok(quote!(Some(#res)))
 			},
 		}
 	}
-	fn to_tokens_move(&self) -> TokenStream {
-		match self {
+	fn to_tokens_move(&self) -> CodeGenResult {
+		let tokens = match self {
 			Value::Px(n) => {
 				quote!(ui::Length::Px(#n))
 			},
+			Value::Length(n, unit) => {
+				let unit = format_ident!("{}", format!("{unit:?}"));
+				quote!(ui::Length::#unit(#n))
+			},
 			Value::Color(r, g, b, a) => {
-				quote!(ui::Color { r: #r, g: #g, b: #b, a: #a })
+				quote!(ui::Brush::Color(ui::Color { r: #r, g: #g, b: #b, a: #a }))
+			},
+			Value::Gradient(Gradient::Linear(angle, stops)) => {
+				let stops = stops.iter().map(color_stop_tokens);
+				quote!(ui::Brush::Linear { angle: #angle, stops: vec![#(#stops),*] })
+			},
+			Value::Gradient(Gradient::Radial(stops)) => {
+				let stops = stops.iter().map(color_stop_tokens);
+				quote!(ui::Brush::Radial { stops: vec![#(#stops),*] })
 			},
 			Value::Int(n) => {
 				quote!(#n)
@@ -549,38 +1045,58 @@ impl Value {
 			Value::String(s) => {
 				quote!(#s.to_owned())
 			},
-			Value::Binding(Expr::Path(path, Ctx::Component)) => {
-				let ident = format_ident!("{}", path.join("."));
-				quote!(this.borrow().#ident)
+			Value::Float(n) => {
+				quote!(#n)
+			},
+			Value::Boolean(b) => {
+				quote!(#b)
 			},
-			Value::Binding(Expr::Path(path, Ctx::Repeater)) => {
-				if path.len() > 1 {
-					let ident = format_ident!("{}", path[1..].join("."));
-					quote!(item.#ident)
-				} else {
-					quote!(item)
-				}
+			Value::Binding(expr, _) => {
+				expr.to_tokens_ref()?
 			},
-			_ => unimplemented!("render values as tokens unimplemented for {:?}", self)
-		}
+			Value::Alignment(a) => {
+				let a = format_ident!("{}", format!("{a:?}"));
+				quote!(ui::Alignment::#a)
+			},
+			Value::Fit(f) => {
+				let f = format_ident!("{}", format!("{f:?}"));
+				quote!(ui::Fit::#f)
+			},
+			Value::Object(_) => {
+				return Err(String::from("object values are not supported here"));
+			},
+			Value::Unset => {
+				return Err(String::from("missing value"));
+			},
+		};
+		Ok(tokens)
 	}
-	fn to_tokens_iter(&self) -> TokenStream {
+	fn to_tokens_iter(&self) -> CodeGenResult {
 		match self {
 			Value::Binding(..) => {
-				let tokens = self.to_tokens_move();
-				quote!(#tokens.iter())
+				let tokens = self.to_tokens_move()?;
+				Ok(quote!(#tokens.iter()))
+			},
+			Value::Int(n) => {
+				Ok(quote!(0..#n))
 			},
 			_ => {
-				let tokens = self.to_tokens_move();
-				quote!(ui::Iterable::iter(&#tokens))
+				Err(String::from("only lists and integers can be repeated over"))
 			}
 		}
 	}
-	fn to_tokens(&self) -> TokenStream {
+	// an owned value of the binding's own type, where there is no target type to convert to
+	fn to_tokens_owned(&self) -> CodeGenResult {
+		match self {
+			Value::Binding(expr, _) => expr.to_tokens(),
+			_ => self.to_tokens_move(),
+		}
+	}
+	fn to_tokens(&self) -> CodeGenResult {
 		match self {
 			Value::Binding(..) => {
-				let tokens = self.to_tokens_move();
-				quote!(ui::Convert::convert(&#tokens))
+				let tokens = self.to_tokens_move()?;
+				Ok(quote!(ui::Convert::convert(&#tokens)))
 			},
 			_ => {
 				self.to_tokens_move()
@@ -589,22 +1105,187 @@ impl Value {
 	}
 }
 
+impl Collection {
+	// ranges and literal lists are iterated in place, without building a `ui::Iterable`
+	fn to_tokens_iter(&self) -> CodeGenResult {
+		match self {
+			Collection::Value(value) => value.to_tokens_iter(),
+			Collection::Range { start, end, inclusive, step } => {
+				let start = start.to_tokens_owned()?;
+				let end = end.to_tokens_owned()?;
+				let range = if *inclusive {
+					quote!(#start..=#end)
+				} else {
+					quote!(#start..#end)
+				};
+				match step {
+					Some(step) => {
+						let step = step.to_tokens_owned()?;
+						Ok(quote!((#range).step_by((#step).max(1) as usize)))
+					},
+					None => Ok(range),
+				}
+			},
+			Collection::Array(values) => {
+				let values = values.iter()
+					.map(|e| e.to_tokens_owned())
+					.collect::<Result<Vec<_>, _>>()?;
+				Ok(quote!(IntoIterator::into_iter([#(#values),*])))
+			},
+		}
+	}
+}
+
+// past the 63rd, props share the last bit, so that a change to any of them is taken as a change
+// to all; that only rebuilds more than needed, never less
+fn prop_mask<'a, I: IntoIterator<Item = &'a String>>(props: &[String], deps: I) -> u64 {
+	deps.into_iter()
+		.filter_map(|dep| props.iter().position(|e| e == dep))
+		.fold(0, |mask, i| mask | 1 << i.min(63))
+}
+
+fn sorted_props(decls: &HashMap<String, PropDecl>) -> Vec<String> {
+	let mut props: Vec<_> = decls.keys().cloned().collect();
+	props.sort();
+	props
+}
+
+// prefixed so that loop variables can't clash with the locals of the generated code
+fn loop_var(name: &str) -> Ident {
+	format_ident!("loop_{}", name)
+}
+
+// the edits drained from the list prop `name`
+fn edits_var(name: &str) -> Ident {
+	format_ident!("edits_{}", name)
+}
+
+impl Expr {
+	// a place expression where possible, so that callers can borrow instead of cloning
+	fn to_tokens_ref(&self) -> CodeGenResult {
+		match self {
+			Expr::Path(path, Ctx::Component) => {
+				let fields = path.iter().map(|e| format_ident!("{}", e));
+				Ok(quote!(this.borrow() #(.#fields)*))
+			},
+			Expr::Path(path, Ctx::Repeater) => {
+				let var = loop_var(&path[0]);
+				let fields = path[1..].iter().map(|e| format_ident!("{}", e));
+				Ok(quote!(#var #(.#fields)*))
+			},
+			Expr::Path(_, Ctx::Element) => {
+				Err(String::from("bindings to `self` are not supported"))
+			},
+			_ => self.to_tokens(),
+		}
+	}
+	fn to_tokens(&self) -> CodeGenResult {
+		let tokens = match self {
+			// resolved against the parent's content box by the runtime, see `check_parent_refs`
+			Expr::Path(_, Ctx::Parent) => {
+				quote!(ui::Length::Percent(100.0))
+			},
+			Expr::Path(..) => {
+				let place = self.to_tokens_ref()?;
+				quote!(#place.clone())
+			},
+			Expr::Literal(value) => {
+				value.to_tokens_move()?
+			},
+			Expr::Unary(op, operand) => {
+				let operand = operand.to_tokens()?;
+				match op {
+					UnaryOp::Not => quote!((!#operand)),
+					UnaryOp::Neg => quote!((-#operand)),
+				}
+			},
+			Expr::Binary(BinaryOp::Concat, lhs, rhs) => {
+				let lhs = lhs.to_tokens()?;
+				let rhs = rhs.to_tokens()?;
+				quote!(format!("{}{}", #lhs, #rhs))
+			},
+			// dividing by zero gives zero rather than panicking, as does the one division that
+			// overflows, `i32::MIN / -1`
+			Expr::Binary(op @ (BinaryOp::IntDiv|BinaryOp::IntRem), lhs, rhs) => {
+				let lhs = lhs.to_tokens()?;
+				let rhs = rhs.to_tokens()?;
+				let f = if *op == BinaryOp::IntDiv { quote!(checked_div) } else { quote!(checked_rem) };
+				quote!(((#lhs).#f(#rhs).unwrap_or(0)))
+			},
+			Expr::Binary(op, lhs, rhs) => {
+				let lhs = lhs.to_tokens()?;
+				let rhs = rhs.to_tokens()?;
+				let op: TokenStream = op.symbol().parse().unwrap();
+				quote!((#lhs #op #rhs))
+			},
+			Expr::Ternary(condition, then, otherwise) => {
+				let condition = condition.to_tokens()?;
+				let then = then.to_tokens()?;
+				let otherwise = otherwise.to_tokens()?;
+				quote!((if #condition { #then } else { #otherwise }))
+			},
+			Expr::Call(name, args) => {
+				match (name.as_str(), args.as_slice()) {
+					("len", [list]) => {
+						let list = list.to_tokens_ref()?;
+						quote!((#list.len() as i32))
+					},
+					("floor"|"ceil"|"round", [arg]) => {
+						let f = format_ident!("{}", name);
+						let arg = arg.to_tokens()?;
+						quote!(((#arg).#f() as i32))
+					},
+					_ => {
+						let f = format_ident!("{}", name);
+						let receiver = args[0].to_tokens()?;
+						let args = args[1..].iter().map(|e| e.to_tokens()).collect::<Result<Vec<_>, _>>()?;
+						quote!((#receiver).#f(#(#args),*))
+					},
+				}
+			},
+			Expr::Cast(operand, t) => {
+				let operand = operand.to_tokens()?;
+				let t = t.to_tokens()?;
+				quote!(ui::Convert::<#t>::convert(&#operand))
+			},
+		};
+		Ok(tokens)
+	}
+}
+
 pub trait CodeGen {
-	fn generate(&self, _element_data: ElementData, _ctx: &mut CodeGenCtx) -> TokenStream {
-		quote!()
+	fn generate(&self, _element_data: ElementData, _ctx: &mut CodeGenCtx) -> CodeGenResult {
+		Ok(quote!())
+	}
+	// runs once the element is in place as `e`, before its children
+	fn generate_in(&self, _element_data: ElementData, _ctx: &mut CodeGenCtx) -> CodeGenResult {
+		Ok(quote!())
+	}
+	// whether the children are handed off to `generate_in` rather than rendered into `e`
+	fn projects_children(&self) -> bool {
+		false
 	}
 }
 
 impl CodeGen for Empty {}
 
 impl CodeGen for Rect {
-	fn generate(&self, _element_data: ElementData, _ctx: &mut CodeGenCtx) -> TokenStream {
-		let x = self.x.to_tokens();
-		let y = self.y.to_tokens();
-		let width = self.width.to_tokens();
-		let height = self.height.to_tokens();
-		let background = self.background.to_tokens();
-		quote!(
+	fn generate(&self, _element_data: ElementData, _ctx: &mut CodeGenCtx) -> CodeGenResult {
+		let x = self.x.to_tokens()?;
+		let y = self.y.to_tokens()?;
+		let width = self.width.to_tokens()?;
+		let height = self.height.to_tokens()?;
+		let background = self.background.to_tokens()?;
+		let border_width = self.border_width.to_tokens()?;
+		let border_color = self.border_color.to_tokens()?;
+		let clip = self.clip.to_tokens()?;
+		let radius = self.radius.to_tokens()?;
+		let shadow_x = self.shadow_x.to_tokens()?;
+		let shadow_y = self.shadow_y.to_tokens()?;
+		let shadow_blur = self.shadow_blur.to_tokens()?;
+		let shadow_color = self.shadow_color.to_tokens()?;
+		let opacity = self.opacity.to_tokens()?;
+		Ok(quote!(
 			let e_impl = ui::ElementImpl::Rect(
 				ui::Rect {
 					bounds: ui::Bounds {
@@ -614,21 +1295,90 @@ impl CodeGen for Rect {
 						height: #height,
 					},
 					color: #background,
+					border: ui::Border {
+						width: #border_width,
+						color: #border_color,
+					},
+					clip: #clip,
+					radius: #radius,
+					shadow: ui::Shadow {
+						x: #shadow_x,
+						y: #shadow_y,
+						blur: #shadow_blur,
+						color: #shadow_color,
+					},
+					opacity: #opacity,
 				}
 			);
-		)
+		))
 	}
 }
 
-impl CodeGen for Scroll {}
+impl CodeGen for Scroll {
+	fn generate(&self, _element_data: ElementData, ctx: &mut CodeGenCtx) -> CodeGenResult {
+		let x = self.x.to_tokens()?;
+		let y = self.y.to_tokens()?;
+		let width = self.width.to_tokens()?;
+		let height = self.height.to_tokens()?;
+		let offset_x = self.offset_x.to_tokens_optional()?;
+		let offset_y = self.offset_y.to_tokens_optional()?;
+
+		// an offset bound straight to one of our fields follows the user's scrolling
+		let mut write_back = Vec::new();
+		let mut args = Vec::new();
+		for (value, arg) in [(&self.offset_x, "x"), (&self.offset_y, "y")] {
+			if let Value::Binding(Expr::Path(path, Ctx::Component), _) = value {
+				let arg = format_ident!("{}", arg);
+				let fields = path.iter().map(|e| format_ident!("{}", e));
+				let bit = ctx.mask(&path[..1]);
+				write_back.push(quote!(
+					this #(.#fields)* = #arg;
+					ui::ComponentBase::dirty(&*this).mark(#bit);
+				));
+				args.push(arg);
+			} else {
+				args.push(format_ident!("_"));
+			}
+		}
+		let binding = if write_back.is_empty() {
+			quote!(Default::default())
+		} else {
+			quote!(
+				{
+					let this = this.clone();
+					ui::ScrollBinding::new(move |#(#args: ui::Length),*| {
+						let mut this = this.borrow_mut();
+						#(#write_back)*
+					})
+				}
+			)
+		};
+
+		Ok(quote!(
+			let e_impl = ui::ElementImpl::Scroll(
+				ui::Scroll {
+					bounds: ui::Bounds {
+						x: #x,
+						y: #y,
+						width: #width,
+						height: #height,
+					},
+					offset_x: #offset_x,
+					offset_y: #offset_y,
+					binding: #binding,
+				}
+			);
+		))
+	}
+}
 
 impl CodeGen for Span {
-	fn generate(&self, _element_data: ElementData, _ctx: &mut CodeGenCtx) -> TokenStream {
-		let x = self.x.to_tokens();
-		let y = self.y.to_tokens();
-		let color = self.color.to_tokens();
-		let max_width = self.max_width.to_tokens_optional();
-		quote!(
+	fn generate(&self, _element_data: ElementData, _ctx: &mut CodeGenCtx) -> CodeGenResult {
+		let x = self.x.to_tokens()?;
+		let y = self.y.to_tokens()?;
+		let color = self.color.to_tokens()?;
+		let max_width = self.max_width.to_tokens_optional()?;
+		Ok(quote!(
 			let e_impl = ui::ElementImpl::Span(
 				ui::Span {
 					x: #x,
@@ -637,7 +1387,7 @@ impl CodeGen for Span {
 					color: #color,
 				}
 			);
-		)
+		))
 		// let mut span = HtmlElement::new("span", &e);
 		// let fit_content = Value::String(String::from("fit-content"));
 		// span.style.width = if let AddedProperties::Layout(layout) = e.added_properties {
@@ -671,18 +1421,407 @@ impl CodeGen for Span {
 }
 
 impl CodeGen for Text {
-	fn generate(&self, _element_data: ElementData, _ctx: &mut CodeGenCtx) -> TokenStream {
-		let content = self.content.to_tokens();
-		quote!(
+	fn generate(&self, _element_data: ElementData, _ctx: &mut CodeGenCtx) -> CodeGenResult {
+		let content = self.content.to_tokens()?;
+		Ok(quote!(
 			let e_impl = ui::ElementImpl::Text(
 				ui::Text {
 					content: #content,
 				}
 			);
-		)
+		))
+	}
+}
+
+impl CodeGen for Img {
+	fn generate(&self, _element_data: ElementData, _ctx: &mut CodeGenCtx) -> CodeGenResult {
+		let x = self.x.to_tokens()?;
+		let y = self.y.to_tokens()?;
+		let width = self.width.to_tokens()?;
+		let height = self.height.to_tokens()?;
+		let src = self.src.to_tokens()?;
+		let fit = self.fit.to_tokens()?;
+		Ok(quote!(
+			let e_impl = ui::ElementImpl::Img(
+				ui::Img {
+					bounds: ui::Bounds {
+						x: #x,
+						y: #y,
+						width: #width,
+						height: #height,
+					},
+					src: #src,
+					fit: #fit,
+				}
+			);
+		))
+	}
+}
+
+impl CodeGen for Window {
+	fn generate(&self, _element_data: ElementData, _ctx: &mut CodeGenCtx) -> CodeGenResult {
+		let title = self.title.to_tokens()?;
+		let width = self.width.to_tokens()?;
+		let height = self.height.to_tokens()?;
+		let min_width = self.min_width.to_tokens_optional()?;
+		let min_height = self.min_height.to_tokens_optional()?;
+		let max_width = self.max_width.to_tokens_optional()?;
+		let max_height = self.max_height.to_tokens_optional()?;
+		let resizable = self.resizable.to_tokens()?;
+		Ok(quote!(
+			let e_impl = ui::ElementImpl::Window(
+				ui::Window {
+					title: #title,
+					width: #width,
+					height: #height,
+					min_width: #min_width,
+					min_height: #min_height,
+					max_width: #max_width,
+					max_height: #max_height,
+					resizable: #resizable,
+				}
+			);
+		))
+	}
+}
+
+impl CodeGen for ComponentInstance {
+	fn generate(&self, _element_data: ElementData, _ctx: &mut CodeGenCtx) -> CodeGenResult {
+		Ok(quote!(
+			let e_impl = ui::ElementImpl::Group;
+		))
+	}
+	fn generate_in(&self, element_data: ElementData, ctx: &mut CodeGenCtx) -> CodeGenResult {
+		let module = format_ident!("{}", self.name.to_case(Case::Snake));
+		let module = if ctx.is_root { quote!(#module) } else { quote!(super::#module) };
+		let struct_name = format_ident!("{}", self.name.to_case(Case::UpperCamel));
+
+		let mut names: Vec<_> = self.properties.keys().collect();
+		names.sort();
+		let mut assignments = Vec::new();
+		for name in names {
+			let value = &self.properties[name];
+			let mut deps = Vec::new();
+			prop_deps(value, &mut deps);
+			let is_callback = self.prop_decls.get(name).map(|e| e.prop_type == Type::Callback).unwrap_or(false);
+			let value = match (is_callback, value) {
+				(true, Value::Binding(expr, _)) => {
+					let callback = expr.to_tokens_ref()?;
+					quote!(ui::Callback::forward(#callback.bind(&this)))
+				},
+				_ => value.to_tokens()?,
+			};
+			let set = format_ident!("set_{}", name);
+			let assignment = quote!(instance.#set(#value););
+			// a new instance starts from its defaults, so it takes every prop passed to it
+			assignments.push(match (ctx.loop_vars.is_empty(), ctx.mask(&deps)) {
+				(false, _) => assignment,
+				(true, 0) => quote!(if fresh { #assignment }),
+				(true, mask) => quote!(if fresh || changes & #mask != 0 { #assignment }),
+			});
+		}
+
+		// the children are rendered by the instance, but their bindings still refer to us
+		let mut children = Vec::new();
+		for (i, child) in element_data.children.iter().enumerate() {
+			ctx.index = i;
+			children.push(match child {
+				Content::Element(child) => {
+					let tag = &child.tag;
+					let child = codegen_element(child, ctx);
+					quote!(
+						if filter.is_empty() || filter.contains(&#tag) {
+							#child
+						} else {
+							parent.element_out(ui::ElementImpl::Group, #i);
+						}
+					)
+				},
+				Content::Children(c) => {
+					let forward = codegen_slot(c, i);
+					quote!(
+						if filter.is_empty() {
+							#forward
+						} else {
+							parent.element_out(ui::ElementImpl::Group, #i);
+						}
+					)
+				},
+			});
+		}
+		// the slot outlives the loop iteration, so it takes its own copy of each loop variable
+		let mut loop_vars = ctx.loop_vars.clone();
+		loop_vars.sort();
+		loop_vars.dedup();
+		let loop_vars = loop_vars.iter().map(|e| loop_var(e));
+
+		Ok(quote!(
+			{
+				let fresh = !e.has_instance();
+				let instance = e.instance_in(|| #module::#struct_name::new(Default::default()));
+				{
+					let mut instance = instance.borrow_mut();
+					#(#assignments)*
+				}
+				e.set_slot({
+					let this = this.clone();
+					let slot = slot.clone();
+					#(let #loop_vars = #loop_vars.clone();)*
+					ui::Slot::new(move |parent: &mut ui::GenericElement<D>, filter: &[&str]| {
+						#(#children)*
+					})
+				});
+				ui::ComponentBase::update(instance, e);
+			}
+		))
+	}
+	fn projects_children(&self) -> bool {
+		true
+	}
+}
+
+impl CodeGen for Layout {
+	fn generate(&self, _element_data: ElementData, _ctx: &mut CodeGenCtx) -> CodeGenResult {
+		// an unset size is computed by `ui::arrange`
+		let length = |v: &Value| match v {
+			Value::Unset => Ok((quote!(ui::Length::Px(0.0)), true)),
+			v => v.to_tokens().map(|t| (t, false)),
+		};
+		let x = self.rect.x.to_tokens()?;
+		let y = self.rect.y.to_tokens()?;
+		let (width, auto_width) = length(&self.rect.width)?;
+		let (height, auto_height) = length(&self.rect.height)?;
+		let background = self.rect.background.to_tokens()?;
+		let border_width = self.rect.border_width.to_tokens()?;
+		let border_color = self.rect.border_color.to_tokens()?;
+		let clip = self.rect.clip.to_tokens()?;
+		let radius = self.rect.radius.to_tokens()?;
+		let shadow_x = self.rect.shadow_x.to_tokens()?;
+		let shadow_y = self.rect.shadow_y.to_tokens()?;
+		let shadow_blur = self.rect.shadow_blur.to_tokens()?;
+		let shadow_color = self.rect.shadow_color.to_tokens()?;
+		let opacity = self.rect.opacity.to_tokens()?;
+		let padding = self.padding.to_tokens()?;
+		let spacing = self.spacing.to_tokens()?;
+		let column = self.column;
+		let grow = self.grow;
+		Ok(quote!(
+			let e_impl = ui::ElementImpl::Layout(
+				ui::Layout {
+					rect: ui::Rect {
+						bounds: ui::Bounds {
+							x: #x,
+							y: #y,
+							width: #width,
+							height: #height,
+						},
+						color: #background,
+						border: ui::Border {
+							width: #border_width,
+							color: #border_color,
+						},
+						clip: #clip,
+						radius: #radius,
+						shadow: ui::Shadow {
+							x: #shadow_x,
+							y: #shadow_y,
+							blur: #shadow_blur,
+							color: #shadow_color,
+						},
+						opacity: #opacity,
+					},
+					auto_width: #auto_width,
+					auto_height: #auto_height,
+					padding: #padding,
+					spacing: #spacing,
+					column: #column,
+					grow: #grow,
+				}
+			);
+		))
 	}
 }
 
-impl CodeGen for ComponentInstance {}
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::super::{parser, Module, elements::build_component};
+
+	fn prop(name: &str) -> Value {
+		Value::Binding(Expr::Path(vec![name.to_string()], Ctx::Component), SourceSpan::new(0, 0))
+	}
+
+	fn iter_tokens(collection: Collection) -> String {
+		collection.to_tokens_iter().unwrap().to_string()
+	}
+
+	fn build(name: &str, source: &str, imports: &HashMap<String, PathBuf>, components: &HashMap<PathBuf, Component>) -> Component {
+		let path = PathBuf::from(format!("{name}.ui"));
+		let mut parse_tree = parser::parse(source, &path).unwrap();
+		parse_tree.name = name.to_string();
+		let mut module = Module::new(&path, imports, components, &parse_tree.props);
+		let component = build_component(&mut module, &parse_tree);
+		assert!(module.diagnostics.is_empty(), "{:?}", module.diagnostics);
+		component
+	}
+
+	// the native code generated for the component `source`, which can use the components in
+	// `imports` by their names
+	fn generate_source(source: &str, imports: &[(&str, &str)]) -> String {
+		let mut imports_map = HashMap::new();
+		let mut components = HashMap::new();
+		for (name, source) in imports {
+			let component = build(name, source, &HashMap::new(), &HashMap::new());
+			let path = PathBuf::from(format!("{name}.ui"));
+			imports_map.insert(name.to_string(), path.clone());
+			components.insert(path, component);
+		}
+		let component = build("test", source, &imports_map, &components);
+		generate(&component, None, None::<String>, "test", "test.ui", false).unwrap()
+	}
+
+	fn contains(code: &str, tokens: TokenStream) -> bool {
+		code.contains(&tokens.to_string())
+	}
+
+	#[test]
+	fn props_past_the_63rd_share_the_last_bit() {
+		let props: Vec<String> = (0..70).map(|i| format!("p{:02}", i)).collect();
+		assert_eq!(prop_mask(&props, [&props[0], &props[5]]), 1 | 1 << 5);
+		assert_eq!(prop_mask(&props, [&props[62]]), 1 << 62);
+		assert_eq!(prop_mask(&props, [&props[63]]), 1 << 63);
+		assert_eq!(prop_mask(&props, [&props[69]]), 1 << 63);
+		assert_eq!(prop_mask(&props, [&String::from("unknown")]), 0);
+	}
+
+	#[test]
+	fn ranges_are_iterated_in_place() {
+		let range = Collection::Range { start: Value::Int(0), end: Value::Int(10), inclusive: false, step: None };
+		assert_eq!(iter_tokens(range), quote!(0i32..10i32).to_string());
+		let range = Collection::Range { start: Value::Int(1), end: prop("count"), inclusive: true, step: None };
+		assert_eq!(iter_tokens(range), quote!(1i32..=this.borrow().count.clone()).to_string());
+	}
+
+	#[test]
+	fn range_steps_are_at_least_one() {
+		let range = Collection::Range { start: Value::Int(0), end: Value::Int(9), inclusive: true, step: Some(prop("step")) };
+		let expected = quote!((0i32..=9i32).step_by((this.borrow().step.clone()).max(1) as usize));
+		assert_eq!(iter_tokens(range), expected.to_string());
+	}
+
+	#[test]
+	fn arrays_are_iterated_by_value() {
+		let array = Collection::Array(vec![Value::String("a".into()), Value::String("b".into())]);
+		assert_eq!(iter_tokens(array), quote!(IntoIterator::into_iter(["a".to_owned(), "b".to_owned()])).to_string());
+		let array = Collection::Array(vec![Value::Int(1), prop("count")]);
+		assert_eq!(iter_tokens(array), quote!(IntoIterator::into_iter([1i32, this.borrow().count.clone()])).to_string());
+	}
+
+	#[test]
+	fn lists_are_iterated_by_reference() {
+		let list = Collection::Value(prop("items"));
+		assert_eq!(iter_tokens(list), quote!(this.borrow().items.iter()).to_string());
+		assert!(Collection::Value(Value::Boolean(true)).to_tokens_iter().is_err());
+	}
+
+	#[test]
+	fn props_are_written_through_setters_that_mark_them() {
+		let code = generate_source("pub title: String;\n\nspan { (title) }", &[]);
+		assert!(contains(&code, quote!(pub struct Test { title: String, __dirty: ui::Dirty, })));
+		assert!(contains(&code, quote!(
+			pub fn set_title(&mut self, value: String) {
+				self.title = value;
+				self.__dirty.mark(1u64);
+			}
+		)));
+		assert!(contains(&code, quote!(
+			pub fn title_mut(&mut self) -> &mut String {
+				self.__dirty.mark(1u64);
+				&mut self.title
+			}
+		)));
+		// private props get private setters
+		let code = generate_source("n: Int;\n\nspan { (n) }", &[]);
+		assert!(contains(&code, quote!(#[allow(dead_code)] fn set_n(&mut self, value: i32))));
+	}
+
+	#[test]
+	fn int_division_by_zero_gives_zero() {
+		let n = || Box::new(Expr::Path(vec!["n".to_string()], Ctx::Component));
+		let int = |n| Box::new(Expr::Literal(Box::new(Value::Int(n))));
+		let div = Expr::Binary(BinaryOp::IntDiv, n(), int(0));
+		assert_eq!(div.to_tokens().unwrap().to_string(), quote!(((this.borrow().n.clone()).checked_div(0i32).unwrap_or(0))).to_string());
+		let rem = Expr::Binary(BinaryOp::IntRem, int(7), n());
+		assert_eq!(rem.to_tokens().unwrap().to_string(), quote!(((7i32).checked_rem(this.borrow().n.clone()).unwrap_or(0))).to_string());
+	}
+
+	const CHILD: &str = "pub title: String;\npub count: Int;\n\nspan { (title + count) }";
 
-impl CodeGen for Layout {}
+	#[test]
+	fn instances_are_kept_and_updated_with_their_props() {
+		let source = "import \"child.ui\";\n\nn: Int;\nshow: Boolean;\n\nrect {\n\tchild if (show) { title: \"a\"; count: (n); }\n}";
+		let code = generate_source(source, &[("child", CHILD)]);
+		assert!(contains(&code, quote!(
+			let fresh = !e.has_instance();
+			let instance = e.instance_in(|| super::child::Child::new(Default::default()));
+		)));
+		// bound props are passed when they change, constant ones only to a new instance
+		assert!(contains(&code, quote!(
+			if fresh || changes & 1u64 != 0 {
+				instance.set_count(ui::Convert::convert(&this.borrow().n));
+			}
+			if fresh {
+				instance.set_title("a".to_owned());
+			}
+		)));
+		assert!(contains(&code, quote!(ui::ComponentBase::update(instance, e);)));
+		// hiding the instance drops it with its group
+		assert!(contains(&code, quote!(else { let e_impl = ui::ElementImpl::Group; parent.element_out(e_impl, 0usize); })));
+	}
+
+	#[test]
+	fn slots_render_the_children_they_accept() {
+		let card = "rect {\n\trect { @child(span) }\n\trect { @children }\n}";
+		let code = generate_source(card, &[]);
+		assert!(contains(&code, quote!(slot.render(e, &["span"]);)));
+		assert!(contains(&code, quote!(slot.render(e, &[]);)));
+		let source = "import \"card.ui\";\n\nrect {\n\tcard {\n\t\tspan { \"h\" }\n\t\trect { }\n\t}\n}";
+		let code = generate_source(source, &[("card", card)]);
+		// children left out by a filter are taken out of their slot
+		assert!(contains(&code, quote!(if filter.is_empty() || filter.contains(&"span"))));
+		assert!(contains(&code, quote!(else { parent.element_out(ui::ElementImpl::Group, 0usize); })));
+		assert!(contains(&code, quote!(if filter.is_empty() || filter.contains(&"rect"))));
+	}
+
+	#[test]
+	fn else_chains_show_one_branch() {
+		let source = "n: Int;\n\nrect {\n\tspan if (n == 0) { \"zero\" }\n\telse span if (n == 1) { \"one\" }\n\telse \"many\"\n}";
+		let code = generate_source(source, &[]);
+		assert!(contains(&code, quote!(
+			let shown: usize = if ui::Convert::convert(&(this.borrow().n.clone() == 0i32)) {
+				0usize
+			} else {
+				if ui::Convert::convert(&(this.borrow().n.clone() == 1i32)) { 1usize } else { 2usize }
+			};
+			let parent = parent.begin_group(0usize);
+			if shown == 0usize
+		)));
+		// every branch keeps its own slot in the group and is taken out when another is shown
+		assert!(contains(&code, quote!(parent.element_out(e_impl, 0usize);)));
+		assert!(contains(&code, quote!(if shown == 1usize)));
+		assert!(contains(&code, quote!(parent.element_out(e_impl, 1usize);)));
+		assert!(contains(&code, quote!(if shown == 2usize)));
+		assert!(contains(&code, quote!(parent.element_out(e_impl, 2usize);)));
+	}
+
+	#[test]
+	fn loops_bind_their_own_variables() {
+		let source = "pub names: [String];\n\nrect {\n\trect for i: s in (names) {\n\t\tspan for j in 0..(i) { (s + j) }\n\t}\n}";
+		let code = generate_source(source, &[]);
+		assert!(contains(&code, quote!(for loop_s in this.borrow().names.iter())));
+		assert!(contains(&code, quote!(let loop_i = i as i32;)));
+		// the inner loop reads the variables of the outer one
+		assert!(contains(&code, quote!(for loop_j in 0i32..loop_i.clone())));
+		assert!(contains(&code, quote!(format!("{}{}", loop_s.clone(), ui::Convert::<String>::convert(&loop_j.clone())))));
+	}
+}
@@ -0,0 +1,189 @@
+use std::path::{Path, PathBuf};
+use std::fmt::Write;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Span {
+	pub start: usize,
+	pub end: usize,
+}
+
+impl Span {
+	pub fn new(start: usize, end: usize) -> Span {
+		Span { start, end }
+	}
+
+	pub fn to(&self, other: Span) -> Span {
+		Span { start: self.start, end: other.end }
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+	Error,
+	Warning,
+}
+
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+	pub severity: Severity,
+	pub message: String,
+	pub path: PathBuf,
+	pub span: Span,
+}
+
+impl Diagnostic {
+	pub fn error<P: Into<PathBuf>, S: Into<String>>(path: P, span: Span, message: S) -> Self {
+		Diagnostic {
+			severity: Severity::Error,
+			message: message.into(),
+			path: path.into(),
+			span,
+		}
+	}
+
+	pub fn warning<P: Into<PathBuf>, S: Into<String>>(path: P, span: Span, message: S) -> Self {
+		Diagnostic {
+			severity: Severity::Warning,
+			message: message.into(),
+			path: path.into(),
+			span,
+		}
+	}
+
+	pub fn is_error(&self) -> bool {
+		self.severity == Severity::Error
+	}
+
+	// renders the diagnostic in the style of rustc, with the offending source line underlined:
+	//
+	// error: tried to set nonexistent property `colour`
+	//   --> src/simple.ui:12:9
+	//    |
+	// 12 |         colour: #fff;
+	//    |         ^^^^^^^^^^^^
+	pub fn render(&self, source: &str) -> String {
+		let mut buf = String::new();
+		let label = match self.severity {
+			Severity::Error => "error",
+			Severity::Warning => "warning",
+		};
+		writeln!(buf, "{}: {}", label, self.message).unwrap();
+
+		let start = std::cmp::min(self.span.start, source.len());
+		let line_start = source[..start].rfind('\n').map(|i| i + 1).unwrap_or(0);
+		let line_end = source[start..].find('\n').map(|i| start + i).unwrap_or(source.len());
+		let line_number = source[..start].matches('\n').count() + 1;
+		let line = &source[line_start..line_end];
+		let prefix = &source[line_start..start];
+		let column = prefix.chars().count() + 1;
+
+		let gutter = " ".repeat(line_number.to_string().len());
+		writeln!(buf, "{}--> {}:{}:{}", gutter, display_path(&self.path), line_number, column).unwrap();
+		writeln!(buf, "{} |", gutter).unwrap();
+		writeln!(buf, "{} | {}", line_number, expand_tabs(line)).unwrap();
+
+		let end = std::cmp::min(std::cmp::max(self.span.end, start), line_end);
+		let padding = expand_tabs(prefix).chars().count();
+		let width = std::cmp::max(expand_tabs(&source[start..end]).chars().count(), 1);
+		writeln!(buf, "{} | {}{}", gutter, " ".repeat(padding), "^".repeat(width)).unwrap();
+		buf
+	}
+}
+
+fn expand_tabs(s: &str) -> String {
+	s.replace('\t', "    ")
+}
+
+fn display_path(path: &Path) -> String {
+	std::env::current_dir()
+		.ok()
+		.and_then(|dir| path.strip_prefix(dir).ok().map(|p| p.display().to_string()))
+		.unwrap_or_else(|| path.display().to_string())
+}
+
+// prints warnings to stderr and collects rendered errors; returns `Err` if any errors were found
+pub fn emit(diagnostics: &[Diagnostic], source: &str) -> Result<(), String> {
+	let mut sorted: Vec<_> = diagnostics.iter().collect();
+	sorted.sort_by_key(|e| e.span.start);
+
+	let mut errors = String::new();
+	let mut error_count = 0;
+	for diagnostic in sorted {
+		if diagnostic.is_error() {
+			writeln!(errors, "{}", diagnostic.render(source)).unwrap();
+			error_count += 1;
+		} else {
+			eprintln!("{}", diagnostic.render(source));
+		}
+	}
+
+	if error_count > 0 {
+		let plural = if error_count == 1 { "" } else { "s" };
+		write!(errors, "error: aborting due to {} previous error{}", error_count, plural).unwrap();
+		Err(errors)
+	} else {
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn error_at(source: &str, text: &str, message: &str) -> Diagnostic {
+		let start = source.find(text).unwrap();
+		Diagnostic::error("test.ui", Span::new(start, start + text.len()), message)
+	}
+
+	#[test]
+	fn renders_tabs_as_four_spaces() {
+		let source = "rect {\n\tcolour: #fff;\n}";
+		let rendered = error_at(source, "colour: #fff;", "tried to set nonexistent property `colour`").render(source);
+		assert_eq!(rendered, concat!(
+			"error: tried to set nonexistent property `colour`\n",
+			" --> test.ui:2:2\n",
+			"  |\n",
+			"2 |     colour: #fff;\n",
+			"  |     ^^^^^^^^^^^^^\n",
+		));
+	}
+
+	#[test]
+	fn underlines_a_multi_line_span_to_the_end_of_its_first_line() {
+		let source = "\n\n\n\n\n\n\n\n\nrect {\n\t\twidth: 10px;\n}";
+		let rendered = error_at(source, "{\n\t\twidth: 10px;\n}", "unclosed `{`").render(source);
+		assert_eq!(rendered, concat!(
+			"error: unclosed `{`\n",
+			"  --> test.ui:10:6\n",
+			"   |\n",
+			"10 | rect {\n",
+			"   |      ^\n",
+		));
+	}
+
+	#[test]
+	fn emits_errors_in_source_order() {
+		let source = "rect {\n\tx: ;\n\ty: 1px 2px;\n}";
+		let diagnostics = [
+			error_at(source, "2px", "expected one of `;`, `}`, found `2px`"),
+			Diagnostic::warning("test.ui", Span::new(0, 4), "unused"),
+			error_at(source, ";", "expected expression, found `;`"),
+		];
+		assert_eq!(emit(&diagnostics, source), Err(String::from(concat!(
+			"error: expected expression, found `;`\n",
+			" --> test.ui:2:5\n",
+			"  |\n",
+			"2 |     x: ;\n",
+			"  |        ^\n",
+			"\n",
+			"error: expected one of `;`, `}`, found `2px`\n",
+			" --> test.ui:3:9\n",
+			"  |\n",
+			"3 |     y: 1px 2px;\n",
+			"  |            ^^^\n",
+			"\n",
+			"error: aborting due to 2 previous errors",
+		))));
+		assert_eq!(emit(&diagnostics[1..2], source), Ok(()));
+	}
+}
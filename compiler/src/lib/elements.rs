@@ -9,11 +9,18 @@ use super::{
 	parser::Component as ParserComponent,
 	Module,
 	Value,
+	Collection,
 	Alignment,
+	Fit,
+	Unit,
 	Expr,
+	UnaryOp,
+	BinaryOp,
 	Type,
 	Ctx,
 	PropDecl,
+	colors,
+	diagnostic::Span as SourceSpan,
 };
 
 pub use super::parser::Children;
@@ -27,6 +34,7 @@ pub struct EventsSpec {
 	pub pointer_move: bool,
 	pub pointer_in: bool,
 	pub pointer_out: bool,
+	pub scroll: bool,
 }
 
 #[derive(Debug, Default, Clone)]
@@ -37,6 +45,7 @@ pub struct Events {
 	pub pointer_move: Value,
 	pub pointer_in: Value,
 	pub pointer_out: Value,
+	pub scroll: Value,
 }
 
 #[derive(Debug, Clone)]
@@ -52,6 +61,13 @@ impl AddedProperties {
 			AddedProperties::None => SetPropertyResult::Ignore,
 		}
 	}
+
+	fn property_types(&self) -> HashMap<String, Type> {
+		match self {
+			AddedProperties::Layout(item) => item.property_types(),
+			AddedProperties::None => HashMap::new(),
+		}
+	}
 }
 
 pub struct ConstructedElementImpl {
@@ -79,6 +95,8 @@ pub type Constructor = fn(&mut Module, &ParserElement) -> ConstructedElementImpl
 pub trait ElementImpl: Debug + CodeGen {
 	fn set_property(&mut self, _k: &String, _v: &Value) -> SetPropertyResult { SetPropertyResult::Ignore }
 	fn property_types(&self) -> HashMap<String, Type> { HashMap::new() }
+	// the properties resolved against the size of the parent, which may use `parent.*`
+	fn relative_properties(&self) -> &'static [&'static str] { &[] }
 }
 
 #[derive(Debug)]
@@ -89,7 +107,10 @@ impl ElementImpl for Empty {}
 pub struct Repeater {
 	pub index: Option<String>,
 	pub item: String,
-	pub collection: Value,
+	pub collection: Collection,
+	pub key: Option<Value>,
+	// the props the collection and the key read, sorted
+	pub deps: Vec<String>,
 }
 
 #[derive(Debug)]
@@ -101,12 +122,18 @@ pub enum Content {
 #[derive(Debug)]
 pub struct Element {
 	pub tag: String,
+	pub span: SourceSpan,
 	pub condition: Option<Value>,
+	// the next branch of an `if`/`else` chain
+	pub otherwise: Option<Box<Element>>,
 	pub repeater: Option<Repeater>,
 	pub children: Vec<Content>,
 	pub element_impl: Box<dyn ElementImpl>,
 	pub added_properties: AddedProperties,
 	pub events: Events,
+	// the props of the component read by the element itself, sorted; those its repeater reads
+	// are kept with the repeater
+	pub deps: Vec<String>,
 }
 
 #[derive(Debug)]
@@ -114,6 +141,8 @@ pub struct Component {
 	pub root: Element,
 	pub props: HashMap<String, PropDecl>,
 	pub name: String,
+	// every `@child`/`@children` in the tree, checked against the elements passed to an instance
+	pub slots: Vec<Children>,
 }
 
 pub struct ElementData<'a> {
@@ -129,13 +158,16 @@ impl Default for Element {
 	fn default() -> Self {
 		Element {
 			tag: String::from("<empty>"),
+			span: SourceSpan::default(),
 			condition: None,
+			otherwise: None,
 			repeater: None,
 			// data_types: HashMap::new(),
 			children: Vec::new(),
 			element_impl: Box::new(Empty),
 			added_properties: AddedProperties::None,
 			events: Default::default(),
+			deps: Vec::new(),
 		}
 	}
 }
@@ -147,6 +179,9 @@ pub enum SetPropertyResult {
 }
 
 fn set_pointer_events_properties(
+	scope: &mut Module,
+	parse_tree: &ParserElement,
+	prefix: &str,
 	properties: &HashMap<String, Value>,
 	events_spec: &EventsSpec,
 	events: &mut Events
@@ -172,32 +207,44 @@ fn set_pointer_events_properties(
 				events.pointer_out = v.clone();
 			},
 			_ => {
-				eprintln!("tried to set nonexistent property `{k}`");
+				let path = format!("{prefix}{k}");
+				scope.error(parse_tree.property_span(&path), format!("tried to set nonexistent property `{path}`"));
 			}
 		}
 	}
 }
 
 fn set_events_properties(
+	scope: &mut Module,
+	parse_tree: &ParserElement,
+	prefix: &str,
 	properties: &HashMap<String, Value>,
 	events_spec: &EventsSpec,
 	events: &mut Events
 ) {
 	for (k, v) in properties {
+		let path = format!("{prefix}{k}");
 		match k.as_str() {
 			"pointer" => {
 				if let Value::Object(map) = v {
-					set_pointer_events_properties(map, events_spec, events);
+					set_pointer_events_properties(scope, parse_tree, &format!("{path}."), map, events_spec, events);
 				}
 			},
+			"scroll" if events_spec.scroll => {
+				events.scroll = v.clone();
+			},
 			_ => {
-				eprintln!("tried to set nonexistent property `{k}`");
+				scope.error(parse_tree.property_span(&path), format!("tried to set nonexistent property `{path}`"));
 			}
 		}
 	}
 }
 
+#[allow(clippy::too_many_arguments)]
 fn set_properties(
+	scope: &mut Module,
+	parse_tree: &ParserElement,
+	prefix: &str,
 	properties: &HashMap<String, Value>,
 	events_spec: &EventsSpec,
 	events: &mut Events,
@@ -205,10 +252,15 @@ fn set_properties(
 	added_properties: &mut AddedProperties,
 ) {
 	for (k, v) in properties {
+		let path = format!("{prefix}{k}");
+		let span = parse_tree.property_span(&path);
 		if k == "self" {
 			match v {
 				Value::Object(map) => {
 					set_properties(
+						scope,
+						parse_tree,
+						&format!("{path}."),
 						map,
 						events_spec,
 						events,
@@ -218,7 +270,7 @@ fn set_properties(
 					continue;
 				},
 				_ => {
-					eprintln!("tried to set `self` as a property");
+					scope.error(span, "tried to set `self` as a property");
 					continue;
 				},
 			}
@@ -227,7 +279,7 @@ fn set_properties(
 			SetPropertyResult::Set       => { continue },
 			SetPropertyResult::Ignore    => {},
 			SetPropertyResult::TypeError => {
-				eprintln!("type error when setting property `{k}`");
+				scope.error(span, format!("type error when setting property `{path}`"));
 				continue;
 			},
 		}
@@ -235,15 +287,15 @@ fn set_properties(
 			SetPropertyResult::Set       => { continue; },
 			SetPropertyResult::Ignore    => {},
 			SetPropertyResult::TypeError => {
-				eprintln!("type error when setting property `{k}`");
+				scope.error(span, format!("type error when setting property `{path}`"));
 				continue;
 			},
 		}
 		if let ("events", Value::Object(map)) = (k.as_str(), v) {
-			set_events_properties(map, &events_spec, events);
+			set_events_properties(scope, parse_tree, &format!("{path}."), map, &events_spec, events);
 			continue;
 		}
-		eprintln!("tried to set nonexistent property `{k}`");
+		scope.error(span, format!("tried to set nonexistent property `{path}`"));
 	}
 }
 
@@ -254,6 +306,7 @@ fn can_coerce(from: &Type, to: &Type) -> bool {
 
 	let (from, to) = match (from, to) {
 		(_, Type::String) => { return true },
+		(Type::Int, Type::Float) => { return true },
 		(Type::Iter(from), Type::Iter(to)) => { return can_coerce(from, to) },
 		(Type::Object(from), Type::Object(to)) => (from, to),
 		_ => { return false }
@@ -272,17 +325,31 @@ fn can_coerce(from: &Type, to: &Type) -> bool {
 	true
 }
 
-fn check_binding(
-	scope: &mut Module,
-	expected_type: Option<&Type>,
-	path: &[String],
-) -> Option<(Type, Ctx)> {
+fn check_path(scope: &mut Module, span: SourceSpan, path: &[String], ctx: &mut Ctx) -> Option<Type> {
+	match ctx {
+		Ctx::Element => {
+			scope.error(span, "bindings to properties of `self` are not supported");
+			return None;
+		},
+		// the size of the parent's content box, known only once the tree is laid out
+		Ctx::Parent => {
+			if let [field] = path {
+				if field == "width" || field == "height" {
+					return Some(Type::Length);
+				}
+			}
+			scope.error(span, format!("`parent` has no property `{}`; only `width` and `height` are available", path.join(".")));
+			return None;
+		},
+		_ => {},
+	}
+
 	let mut binding_type = None;
-	let mut ctx = Ctx::Component;
+	let mut new_ctx = Ctx::Component;
 	for map in scope.stack.iter().rev() {
 		if let Some(t) = map.get(&path[0]) {
 			binding_type = Some(t);
-			ctx = Ctx::Repeater;
+			new_ctx = Ctx::Repeater;
 			break;
 		}
 	}
@@ -297,68 +364,520 @@ fn check_binding(
 			break;
 		}
 	}
-	match (binding_type, expected_type) {
-		(Some(binding_type), Some(expected_type)) => {
-			if !can_coerce(binding_type, expected_type) {
-				eprintln!("expected type {:?}, found {:?}", expected_type, binding_type);
+	let binding_type = binding_type.cloned();
+	if binding_type.is_none() {
+		scope.error(span, format!("binding to undeclared property `{}`", path.join(".")));
+	} else {
+		*ctx = new_ctx;
+	}
+	binding_type
+}
+
+// each `parent.*` in `expr`, and whether it's only added, subtracted or scaled on the way out,
+// which is all a `100%` standing in for it can do
+fn parent_refs<'a>(expr: &'a Expr, linear: bool, found: &mut Vec<(&'a str, bool)>) {
+	match expr {
+		Expr::Path(path, Ctx::Parent) => found.extend(path.first().map(|e| (e.as_str(), linear))),
+		Expr::Path(..) | Expr::Literal(_) => {},
+		Expr::Unary(op, operand) => parent_refs(operand, linear && *op == UnaryOp::Neg, found),
+		Expr::Cast(operand, _) => parent_refs(operand, false, found),
+		Expr::Binary(op, lhs, rhs) => {
+			let linear = linear && matches!(op, BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Div);
+			parent_refs(lhs, linear, found);
+			parent_refs(rhs, linear, found);
+		},
+		Expr::Ternary(condition, then, otherwise) => {
+			parent_refs(condition, false, found);
+			parent_refs(then, linear, found);
+			parent_refs(otherwise, linear, found);
+		},
+		Expr::Call(_, args) => {
+			for arg in args {
+				parent_refs(arg, false, found);
+			}
+		},
+	}
+}
+
+// the props of the component that `value` reads
+pub fn prop_deps(value: &Value, found: &mut Vec<String>) {
+	match value {
+		Value::Binding(expr, _) => expr_prop_deps(expr, found),
+		Value::Object(map) => {
+			for value in map.values() {
+				prop_deps(value, found);
+			}
+		},
+		_ => {},
+	}
+}
+
+fn expr_prop_deps(expr: &Expr, found: &mut Vec<String>) {
+	match expr {
+		Expr::Path(path, Ctx::Component) => found.extend(path.first().cloned()),
+		Expr::Path(..) => {},
+		Expr::Literal(value) => prop_deps(value, found),
+		Expr::Unary(_, operand) | Expr::Cast(operand, _) => expr_prop_deps(operand, found),
+		Expr::Binary(_, lhs, rhs) => {
+			expr_prop_deps(lhs, found);
+			expr_prop_deps(rhs, found);
+		},
+		Expr::Ternary(condition, then, otherwise) => {
+			expr_prop_deps(condition, found);
+			expr_prop_deps(then, found);
+			expr_prop_deps(otherwise, found);
+		},
+		Expr::Call(_, args) => {
+			for arg in args {
+				expr_prop_deps(arg, found);
+			}
+		},
+	}
+}
+
+fn collection_deps(collection: &Collection, found: &mut Vec<String>) {
+	match collection {
+		Collection::Value(value) => prop_deps(value, found),
+		Collection::Range { start, end, step, .. } => {
+			for value in [start, end].into_iter().chain(step.as_ref()) {
+				prop_deps(value, found);
+			}
+		},
+		Collection::Array(values) => {
+			for value in values {
+				prop_deps(value, found);
+			}
+		},
+	}
+}
+
+// `parent.width` and `parent.height` become `100%`, which like in css is measured along the axis of
+// the property it ends up in, so they're only allowed in the properties an element resolves against
+// its parent (`prop`), and there only on the property's own axis
+fn check_parent_refs(scope: &mut Module, span: SourceSpan, prop: Option<&str>, expr: &Expr) {
+	let mut found = Vec::new();
+	parent_refs(expr, true, &mut found);
+	let (field, linear) = match found.first() {
+		Some(&first) => found.iter().copied().find(|e| !e.1).unwrap_or(first),
+		None => return,
+	};
+	let prop = match prop {
+		Some(prop) => prop,
+		None => {
+			scope.error(span, format!("`parent.{}` can only be used in the position and size of an element", field));
+			return;
+		},
+	};
+	if !linear {
+		scope.error(span, format!("`parent.{}` is only known after layout, so `{}` can only add, subtract or scale it", field, prop));
+		return;
+	}
+	let (own, other) = match prop {
+		"y"|"height" => ("height", "width"),
+		_ => ("width", "height"),
+	};
+	if found.iter().any(|e| e.0 == other) {
+		scope.error(span, format!("`{}` is measured against `parent.{}`, so it can't use `parent.{}`", prop, own, other));
+	}
+}
+
+fn literal_type(value: &Value) -> Option<Type> {
+	match value {
+		Value::Px(_)        => Some(Type::Length),
+		Value::Length(..)   => Some(Type::Length),
+		Value::Float(_)     => Some(Type::Float),
+		Value::Int(_)       => Some(Type::Int),
+		Value::Color(..)    => Some(Type::Brush),
+		Value::Gradient(_)  => Some(Type::Brush),
+		Value::String(_)    => Some(Type::String),
+		Value::Boolean(_)   => Some(Type::Boolean),
+		Value::Alignment(_) => Some(Type::Alignment),
+		Value::Fit(_)       => Some(Type::Fit),
+		_                   => None,
+	}
+}
+
+fn is_numeric(t: &Type) -> bool {
+	*t == Type::Int || *t == Type::Float
+}
+
+fn cast(expr: &mut Expr, to: Type) {
+	if let (Expr::Literal(value), Type::Float) = (&*expr, &to) {
+		if let Value::Int(n) = **value {
+			*expr = Expr::Literal(Box::new(Value::Float(n as f32)));
+			return;
+		}
+	}
+	let inner = std::mem::replace(expr, Expr::Literal(Box::new(Value::Unset)));
+	*expr = Expr::Cast(Box::new(inner), to);
+}
+
+// promotes a pair of numeric operands to a common type, inserting casts where needed
+fn unify_numeric(lhs: &mut Expr, lhs_type: &Type, rhs: &mut Expr, rhs_type: &Type) -> Type {
+	match (lhs_type, rhs_type) {
+		(Type::Int, Type::Float) => { cast(lhs, Type::Float); Type::Float },
+		(Type::Float, Type::Int) => { cast(rhs, Type::Float); Type::Float },
+		_ => lhs_type.clone(),
+	}
+}
+
+fn check_unary(scope: &mut Module, span: SourceSpan, op: UnaryOp, operand: &mut Expr) -> Option<Type> {
+	let t = check_expr(scope, span, operand)?;
+	match (op, &t) {
+		(UnaryOp::Not, Type::Boolean) => Some(t),
+		(UnaryOp::Neg, Type::Int|Type::Float|Type::Length) => Some(t),
+		_ => {
+			scope.error(span, format!("operator `{}` cannot be applied to type {:?}", op.symbol(), t));
+			None
+		},
+	}
+}
+
+fn has_relative_unit(expr: &Expr) -> bool {
+	match expr {
+		Expr::Literal(value) => matches!(**value, Value::Length(_, Unit::Percent|Unit::Em|Unit::Vw|Unit::Vh)),
+		Expr::Path(..) | Expr::Call(..) => false,
+		Expr::Unary(_, operand) | Expr::Cast(operand, _) => has_relative_unit(operand),
+		Expr::Binary(_, lhs, rhs) => has_relative_unit(lhs) || has_relative_unit(rhs),
+		Expr::Ternary(_, then, otherwise) => has_relative_unit(then) || has_relative_unit(otherwise),
+	}
+}
+
+fn check_binary(scope: &mut Module, span: SourceSpan, op: &mut BinaryOp, lhs: &mut Expr, rhs: &mut Expr) -> Option<Type> {
+	let lhs_type = check_expr(scope, span, lhs)?;
+	let rhs_type = check_expr(scope, span, rhs)?;
+	let numeric = is_numeric(&lhs_type) && is_numeric(&rhs_type);
+
+	// relative lengths are only known after layout, and until then none of them orders
+	let comparison = matches!(op, BinaryOp::Eq|BinaryOp::Ne|BinaryOp::Lt|BinaryOp::Le|BinaryOp::Gt|BinaryOp::Ge);
+	if comparison && lhs_type == Type::Length && (has_relative_unit(lhs) || has_relative_unit(rhs)) {
+		scope.error(span, "relative lengths can't be compared, only lengths in absolute units");
+		return None;
+	}
+
+	let result = match *op {
+		BinaryOp::Or|BinaryOp::And if lhs_type == Type::Boolean && rhs_type == Type::Boolean => {
+			Some(Type::Boolean)
+		},
+		BinaryOp::Eq|BinaryOp::Ne if numeric => {
+			unify_numeric(lhs, &lhs_type, rhs, &rhs_type);
+			Some(Type::Boolean)
+		},
+		BinaryOp::Eq|BinaryOp::Ne if lhs_type == rhs_type => {
+			Some(Type::Boolean)
+		},
+		BinaryOp::Lt|BinaryOp::Le|BinaryOp::Gt|BinaryOp::Ge if numeric => {
+			unify_numeric(lhs, &lhs_type, rhs, &rhs_type);
+			Some(Type::Boolean)
+		},
+		BinaryOp::Lt|BinaryOp::Le|BinaryOp::Gt|BinaryOp::Ge
+			if lhs_type == Type::Length && rhs_type == Type::Length => {
+			Some(Type::Boolean)
+		},
+		BinaryOp::Add if lhs_type == Type::String || rhs_type == Type::String => {
+			for (operand, t) in [(&mut *lhs, &lhs_type), (&mut *rhs, &rhs_type)] {
+				match t {
+					Type::String => {},
+					Type::Int|Type::Float|Type::Boolean => cast(operand, Type::String),
+					_ => {
+						scope.error(span, format!("cannot concatenate value of type {:?} with a String", t));
+						return None;
+					},
+				}
+			}
+			*op = BinaryOp::Concat;
+			Some(Type::String)
+		},
+		BinaryOp::Add|BinaryOp::Sub|BinaryOp::Mul|BinaryOp::Div|BinaryOp::Rem if numeric => {
+			let t = unify_numeric(lhs, &lhs_type, rhs, &rhs_type);
+			if t == Type::Int {
+				*op = match *op {
+					BinaryOp::Div => BinaryOp::IntDiv,
+					BinaryOp::Rem => BinaryOp::IntRem,
+					op => op,
+				};
+			}
+			Some(t)
+		},
+		BinaryOp::Add|BinaryOp::Sub if lhs_type == Type::Length && rhs_type == Type::Length => {
+			Some(Type::Length)
+		},
+		BinaryOp::Mul|BinaryOp::Div if lhs_type == Type::Length && is_numeric(&rhs_type) => {
+			if rhs_type == Type::Int {
+				cast(rhs, Type::Float);
+			}
+			Some(Type::Length)
+		},
+		BinaryOp::Mul if is_numeric(&lhs_type) && rhs_type == Type::Length => {
+			if lhs_type == Type::Int {
+				cast(lhs, Type::Float);
+			}
+			Some(Type::Length)
+		},
+		_ => None,
+	};
+
+	if result.is_none() {
+		scope.error(span, format!(
+			"operator `{}` cannot be applied to types {:?} and {:?}",
+			op.symbol(),
+			lhs_type,
+			rhs_type,
+		));
+	}
+	result
+}
+
+fn check_ternary(
+	scope: &mut Module,
+	span: SourceSpan,
+	condition: &mut Expr,
+	then: &mut Expr,
+	otherwise: &mut Expr,
+) -> Option<Type> {
+	check_expr_type(scope, span, Some(&Type::Boolean), condition);
+	let then_type = check_expr(scope, span, then)?;
+	let otherwise_type = check_expr(scope, span, otherwise)?;
+	if is_numeric(&then_type) && is_numeric(&otherwise_type) {
+		Some(unify_numeric(then, &then_type, otherwise, &otherwise_type))
+	} else if then_type == otherwise_type {
+		Some(then_type)
+	} else {
+		scope.error(span, format!(
+			"branches of conditional expression have different types: {:?} and {:?}",
+			then_type,
+			otherwise_type,
+		));
+		None
+	}
+}
+
+fn check_call(scope: &mut Module, span: SourceSpan, name: &str, args: &mut [Expr]) -> Option<Type> {
+	let mut arg_types = Vec::new();
+	for arg in args.iter_mut() {
+		arg_types.push(check_expr(scope, span, arg)?);
+	}
+
+	let arity = match name {
+		"abs"|"floor"|"ceil"|"round"|"sqrt"|"len" => 1,
+		"min"|"max" => 2,
+		"clamp" => 3,
+		_ => {
+			scope.error(span, format!("call to unknown function `{}`", name));
+			return None;
+		},
+	};
+	if args.len() != arity {
+		scope.error(span, format!("function `{}` takes {} argument(s), but {} were supplied", name, arity, args.len()));
+		return None;
+	}
+
+	match name {
+		"len" => {
+			if let Type::Iter(_) = arg_types[0] {
+				return Some(Type::Int);
 			}
 		},
-		(None, _) => {
-			eprintln!("binding to undeclared property: {}", path.join("."));
+		_ if arg_types.iter().all(is_numeric) => {
+			let common = if arg_types.contains(&Type::Float) { Type::Float } else { Type::Int };
+			let param = match name {
+				"floor"|"ceil"|"round"|"sqrt" => Type::Float,
+				_ => common.clone(),
+			};
+			for (arg, t) in args.iter_mut().zip(arg_types.iter()) {
+				if *t != param {
+					cast(arg, param.clone());
+				}
+			}
+			return match name {
+				"floor"|"ceil"|"round" => Some(Type::Int),
+				"sqrt" => Some(Type::Float),
+				_ => Some(common),
+			};
 		},
 		_ => {},
 	}
-	binding_type.map(|t| (t.clone(), ctx))
+
+	scope.error(span, format!("invalid argument type(s) for function `{}`: {:?}", name, arg_types));
+	None
+}
+
+// a css color name in an expression, unless a prop or repeater variable goes by the same name
+fn named_color(scope: &Module, path: &[String]) -> Option<Value> {
+	let name = match path {
+		[name] => name,
+		_ => return None,
+	};
+	if scope.props.contains_key(name) || scope.stack.iter().any(|map| map.contains_key(name)) {
+		return None;
+	}
+	colors::named_color(name).map(|(r, g, b, a)| Value::Color(r, g, b, a))
+}
+
+fn check_expr(scope: &mut Module, span: SourceSpan, expr: &mut Expr) -> Option<Type> {
+	match expr {
+		Expr::Path(path, ctx) => {
+			if let (Ctx::Component, Some(color)) = (*ctx, named_color(scope, path)) {
+				*expr = Expr::Literal(Box::new(color));
+				return Some(Type::Brush);
+			}
+			check_path(scope, span, path, ctx)
+		},
+		Expr::Literal(value) => literal_type(value),
+		Expr::Unary(op, operand) => check_unary(scope, span, *op, operand),
+		Expr::Binary(op, lhs, rhs) => check_binary(scope, span, op, lhs, rhs),
+		Expr::Ternary(condition, then, otherwise) => check_ternary(scope, span, condition, then, otherwise),
+		Expr::Call(name, args) => check_call(scope, span, name, args),
+		Expr::Cast(_, t) => Some(t.clone()),
+	}
+}
+
+fn check_expr_type(
+	scope: &mut Module,
+	span: SourceSpan,
+	expected_type: Option<&Type>,
+	expr: &mut Expr,
+) -> Option<Type> {
+	let binding_type = check_expr(scope, span, expr);
+	if let (Some(binding_type), Some(expected_type)) = (&binding_type, expected_type) {
+		if !can_coerce(binding_type, expected_type) {
+			scope.error(span, format!("expected type {:?}, found {:?}", expected_type, binding_type));
+		}
+	}
+	binding_type
+}
+
+// `parent_axis` is the property the binding sets, if the element resolves it against its parent
+fn check_binding(
+	scope: &mut Module,
+	span: SourceSpan,
+	expected_type: Option<&Type>,
+	parent_axis: Option<&str>,
+	expr: &mut Expr,
+) -> Option<Type> {
+	let binding_type = check_expr_type(scope, span, expected_type, expr);
+	check_parent_refs(scope, span, parent_axis, expr);
+	binding_type
 }
 
 fn check_and_push_repeater_bindings(
 	scope: &mut Module,
+	span: SourceSpan,
 	repeater: &mut Option<Repeater>,
 ) {
 	let map = if let Some(repeater) = repeater.as_mut() {
-		match &mut repeater.collection {
-			Value::Binding(Expr::Path(path, ref mut ctx)) => {
-				let t = check_binding(scope, Some(&Type::Iter(Box::new(Type::Any))), &path);
-				let item_type = if let Some((Type::Iter(t), new_ctx)) = t {
-					*ctx = new_ctx;
-					*t.clone()
+		let item_type = match &mut repeater.collection {
+			Collection::Value(Value::Binding(expr, span)) => {
+				let t = check_binding(scope, *span, Some(&Type::Iter(Box::new(Type::Any))), None, expr);
+				if let Some(Type::Iter(t)) = t {
+					*t
 				} else {
 					Type::Any
-				};
-				let mut map = hashmap![repeater.item.clone() => item_type];
-				if let Some(index) = &repeater.index {
-					map.insert(index.clone(), Type::Int);
 				}
-				map
 			},
-			Value::Int(_) => {
-				let mut map = hashmap![repeater.item.clone() => Type::Int];
-				if let Some(index) = &repeater.index {
-					map.insert(index.clone(), Type::Int);
+			Collection::Value(Value::Int(_)) => Type::Int,
+			Collection::Value(value) => {
+				let found = literal_type(value).map(|t| format!("{:?}", t)).unwrap_or_else(|| String::from("an object"));
+				scope.error(span, format!("cannot repeat over a value of type {}", found));
+				Type::Any
+			},
+			Collection::Range { start, end, step, .. } => {
+				check_range_bound(scope, span, start);
+				check_range_bound(scope, span, end);
+				if let Some(step) = step {
+					if let Value::Int(n) = step {
+						if *n <= 0 {
+							scope.error(span, "the step of a range must be positive");
+						}
+					}
+					check_range_bound(scope, span, step);
 				}
-				map
-			}
-			x => {
-				unimplemented!("{:?}", x);
+				Type::Int
 			},
+			Collection::Array(values) => array_item_type(scope, span, values),
+		};
+		let mut map = hashmap![repeater.item.clone() => item_type];
+		if let Some(index) = &repeater.index {
+			map.insert(index.clone(), Type::Int);
 		}
+		map
 	} else {
 		HashMap::new()
 	};
 
 	scope.stack.push(map);
+
+	// the key is evaluated per item, so it sees the loop variables
+	if let Some(Value::Binding(expr, span)) = repeater.as_mut().and_then(|e| e.key.as_mut()) {
+		match check_binding(scope, *span, None, None, expr) {
+			Some(Type::Int|Type::String|Type::Any)|None => {},
+			Some(t) => scope.error(*span, format!("expected type Int or String for key, found {:?}", t)),
+		}
+	}
+}
+
+fn check_range_bound(scope: &mut Module, span: SourceSpan, bound: &mut Value) {
+	if let Value::Binding(expr, span) = bound {
+		check_binding(scope, *span, Some(&Type::Int), None, expr);
+	} else if !matches!(bound, Value::Int(_)) {
+		scope.error(span, "the bounds of a range must be of type Int");
+	}
+}
+
+// the elements of a literal list must all have the same type, which its items take
+fn array_item_type(scope: &mut Module, span: SourceSpan, values: &mut [Value]) -> Type {
+	let mut item_type: Option<Type> = None;
+	for value in values.iter_mut() {
+		let t = match value {
+			Value::Binding(expr, span) => check_binding(scope, *span, None, None, expr),
+			value => match literal_type(value) {
+				Some(t) => Some(t),
+				None => {
+					scope.error(span, "the elements of a list must be plain values");
+					None
+				},
+			},
+		};
+		match (&item_type, t) {
+			(None, Some(t)) => item_type = Some(t),
+			(Some(expected), Some(t)) if t != *expected => {
+				scope.error(span, format!("expected type {:?} for every element of the list, found {:?}", expected, t));
+			},
+			_ => {},
+		}
+	}
+	if values.is_empty() {
+		scope.error(span, "cannot repeat over an empty list");
+	}
+	item_type.unwrap_or(Type::Any)
+}
+
+// the handlers an element can be given under `events`
+fn events_type() -> Type {
+	let pointer = ["click", "press", "release", "move", "in", "out"]
+		.into_iter()
+		.map(|e| (String::from(e), Type::Callback))
+		.collect();
+	Type::Object(hashmap![
+		String::from("pointer") => Type::Object(pointer),
+		String::from("scroll") => Type::Callback,
+	])
 }
 
 fn check_bindings(
 	scope: &mut Module,
+	span: SourceSpan,
 	expected_prop_types: &HashMap<String, Type>,
+	relative_props: &[&str],
 	received_props: &mut HashMap<String, Value>,
 	condition: &mut Option<Value>,
 ) {
 	fn check_prop_bindings(
 		scope: &mut Module,
+		span: SourceSpan,
 		received_props: &mut HashMap<String, Value>,
-		expected_types: Option<&HashMap<String, Type>>
+		expected_types: Option<&HashMap<String, Type>>,
+		relative_props: &[&str],
 	) {
 		for (k, v) in received_props {
 			let expected_type = expected_types.and_then(|e| e.get(k));
@@ -368,48 +887,35 @@ fn check_bindings(
 						Some(Type::Object(map)) => Some(map),
 						_ => None,
 					};
-					check_prop_bindings(scope, map, expected_types);
+					check_prop_bindings(scope, span, map, expected_types, &[]);
 				},
-				Value::Binding(Expr::Path(path, ref mut ctx)) => {
-					if let Some((_, new_ctx)) = check_binding(scope, expected_type, path) {
-						*ctx = new_ctx;
-					}
+				Value::Binding(expr, span) => {
+					let parent_axis = relative_props.iter().find(|e| *e == k).copied();
+					check_binding(scope, *span, expected_type, parent_axis, expr);
+				},
+				// a callback can only come from a binding
+				value if expected_type == Some(&Type::Callback) => {
+					let found = literal_type(value).map(|t| format!("{:?}", t)).unwrap_or_else(|| String::from("an object"));
+					scope.error(span, format!("expected type Callback for `{}`, found {}", k, found));
 				},
 				_ => {},
 			}
 		}
 	}
-	check_prop_bindings(scope, received_props, Some(expected_prop_types));
-
-	// macro_rules! check_event_binding {
-	// 	($id:ident) => {
-	// 		match &events.$id {
-	// 			Value::Binding(Expr::Path(path, Ctx::Component)) => {
-	// 				check_binding(scope, Some(&Type::Callback), &path);
-	// 			},
-	// 			Value::Unset => {},
-	// 			_ => {
-	// 				unimplemented!();
-	// 			},
-	// 		}
-	// 	};
-	// }
-	// check_event_binding!(pointer_click);
-	// check_event_binding!(pointer_press);
-	// check_event_binding!(pointer_release);
-	// check_event_binding!(pointer_move);
-	// check_event_binding!(pointer_in);
-	// check_event_binding!(pointer_out);
+	// handlers are bindings like any other, which have to be callbacks
+	let mut expected_prop_types = expected_prop_types.clone();
+	expected_prop_types.entry(String::from("events")).or_insert_with(events_type);
+	check_prop_bindings(scope, span, received_props, Some(&expected_prop_types), relative_props);
 
 	if let Some(condition) = condition.as_mut() {
 		match condition {
-			Value::Binding(Expr::Path(path, ref mut ctx)) => {
-				if let Some((_, new_ctx)) = check_binding(scope, Some(&Type::Boolean), &path) {
-					*ctx = new_ctx;
-				}
+			Value::Binding(expr, span) => {
+				check_binding(scope, *span, Some(&Type::Boolean), None, expr);
 			},
-			_ => {
-				unimplemented!();
+			Value::Boolean(_) => {},
+			value => {
+				let found = literal_type(value).map(|t| format!("{:?}", t)).unwrap_or_else(|| String::from("an object"));
+				scope.error(span, format!("expected type Boolean for condition, found {}", found));
 			},
 		}
 	}
@@ -420,33 +926,64 @@ impl Element {
 		scope: &mut Module,
 		parse_tree: &ParserElement,
 		mut added_properties: AddedProperties,
-	) -> Result<Self, String> {
+	) -> Option<Self> {
 		let mut repeater = parse_tree.repeater.as_ref().map(|e| Repeater {
 			index: e.index.as_ref().map(|e| e.into()),
 			item: e.item.clone(),
 			collection: e.collection.clone(),
+			key: e.key.clone(),
+			deps: Vec::new(),
 		});
 
-		check_and_push_repeater_bindings(scope, &mut repeater);
+		check_and_push_repeater_bindings(scope, parse_tree.span, &mut repeater);
 
 		let ConstructedElementImpl {
 			mut element_impl,
 			children,
 			events_spec,
-		} = scope.construct(&parse_tree)?;
+		} = match scope.construct(parse_tree) {
+			Some(constructed) => constructed,
+			None => {
+				scope.stack.pop();
+				return None;
+			},
+		};
 
 		let mut condition = parse_tree.condition.clone();
 		let mut properties = parse_tree.properties.clone();
 
+		let mut property_types = element_impl.property_types();
+		property_types.extend(added_properties.property_types());
 		check_bindings(
 			scope,
-			&element_impl.property_types(),
+			parse_tree.span,
+			&property_types,
+			element_impl.relative_properties(),
 			&mut properties,
 			&mut condition,
 		);
 
+		let mut deps = Vec::new();
+		for value in properties.values().chain(condition.iter()) {
+			prop_deps(value, &mut deps);
+		}
+		deps.sort();
+		deps.dedup();
+		if let Some(repeater) = repeater.as_mut() {
+			collection_deps(&repeater.collection, &mut repeater.deps);
+			if let Some(key) = repeater.key.as_ref() {
+				prop_deps(key, &mut repeater.deps);
+			}
+			repeater.deps.sort();
+			repeater.deps.dedup();
+		}
+
 		let mut events = Events::default();
+		let branch_properties = added_properties.clone();
 		set_properties(
+			scope,
+			parse_tree,
+			"",
 			&properties,
 			&events_spec,
 			&mut events,
@@ -454,21 +991,31 @@ impl Element {
 			&mut added_properties,
 		);
 
-		if repeater.is_some() {
-			scope.stack.pop();
-		}
+		scope.stack.pop();
+
+		let otherwise = parse_tree.otherwise.as_ref().and_then(|branch| {
+			Element::construct_element(scope, branch, branch_properties).map(Box::new)
+		});
 
-		Ok(Element {
+		Some(Element {
 			tag: parse_tree.path.join("."),
+			span: parse_tree.span,
 			condition,
+			otherwise,
 			repeater,
 			children,
 			element_impl,
 			added_properties,
 			events,
+			deps,
 		})
 	}
 
+	// the element followed by the rest of its `if`/`else` chain
+	pub fn branches(&self) -> impl Iterator<Item = &Element> {
+		std::iter::successors(Some(self), |e| e.otherwise.as_deref())
+	}
+
 	pub fn data(&self) -> ElementData {
 		ElementData {
 			tag: &self.tag,
@@ -494,9 +1041,8 @@ fn build_elements_with_added_properties(
 	for item in parse_tree {
 		match item {
 			ParserContent::Element(e) => {
-				match Element::construct_element(scope, e, added_properties.clone()) {
-					Ok(element) => elements.push(Content::Element(element)),
-					Err(message) => eprintln!("Error: {}", message)
+				if let Some(element) = Element::construct_element(scope, e, added_properties.clone()) {
+					elements.push(Content::Element(element));
 				}
 			},
 			ParserContent::Children(c) => {
@@ -520,41 +1066,140 @@ fn build_elements_with_added_properties(
 	elements
 }
 
-pub fn build_component(scope: &mut Module, parse_tree: &ParserComponent) -> Component {
-	match Element::construct_element(scope, &parse_tree.root, AddedProperties::None) {
-		Ok(root) => {
-			Component { root, props: parse_tree.props.clone(), name: parse_tree.name.clone() }
-		},
-		Err(message) => {
-			eprintln!("Error: {}", message);
-			Component { root: Element::default(), props: parse_tree.props.clone(), name: parse_tree.name.clone() }
+// defaults are restricted to literals, with Int literals widened in place where a Float is declared
+fn check_prop_defaults(scope: &mut Module, props: &mut HashMap<String, PropDecl>) {
+	for decl in props.values_mut() {
+		let default = match decl.default.as_mut() {
+			Some(default) => default,
+			None => continue,
+		};
+		match (&decl.prop_type, &*default) {
+			(Type::Callback, _) => {
+				scope.error(decl.span, format!("callback `{}` cannot have a default value", decl.name));
+			},
+			(_, Value::Binding(..)) => {
+				scope.error(decl.span, format!("default value of `{}` must be a literal", decl.name));
+			},
+			(_, Value::Object(_)) | (Type::Iter(_), _) | (Type::Object(_), _) => {
+				scope.error(decl.span, format!("default values for properties of type {:?} are not supported yet", decl.prop_type));
+			},
+			(Type::Float, Value::Int(n)) => {
+				*default = Value::Float(*n as f32);
+			},
+			(expected_type, value) => {
+				match literal_type(value) {
+					Some(found) if found == *expected_type => {},
+					found => {
+						let found = found.map(|t| format!("{:?}", t)).unwrap_or_else(|| String::from("an object"));
+						scope.error(decl.span, format!("expected type {:?} for default value of `{}`, found {}", expected_type, decl.name, found));
+					},
+				}
+			},
 		}
 	}
 }
 
-// #[derive(Debug)]
-// pub struct Window {
-// 	pub standard_props: StandardProps,
-// 	pub title: Value,
-// 	pub children: Vec<Box<dyn Element>>,
-// }
+// a `window` only makes sense as the outermost element
+fn check_windows(scope: &mut Module, content: &[Content]) {
+	for item in content {
+		if let Content::Element(e) = item {
+			for e in e.branches() {
+				if e.tag == "window" {
+					scope.error(e.span, "`window` can only be the root element of a component");
+				}
+				check_windows(scope, &e.children);
+			}
+		}
+	}
+}
 
-// impl ElementImpl for Window {}
+fn collect_slots(content: &[Content], slots: &mut Vec<Children>) {
+	for item in content {
+		match item {
+			Content::Element(e) => {
+				for e in e.branches() {
+					collect_slots(&e.children, slots);
+				}
+			},
+			Content::Children(c) => slots.push(c.clone()),
+		}
+	}
+}
+
+pub fn build_component(scope: &mut Module, parse_tree: &ParserComponent) -> Component {
+	let mut props = parse_tree.props.clone();
+	check_prop_defaults(scope, &mut props);
+	let root = Element::construct_element(scope, &parse_tree.root, AddedProperties::None).unwrap_or_default();
+	check_windows(scope, &root.children);
+	let mut slots = Vec::new();
+	collect_slots(&root.children, &mut slots);
+	Component { root, props, name: parse_tree.name.clone(), slots }
+}
+
+// the window a native app opens, configured by the root element of its component; on the web
+// only the title applies
+#[derive(Debug)]
+pub struct Window {
+	pub title: Value,
+	pub width: Value,
+	pub height: Value,
+	pub min_width: Value,
+	pub min_height: Value,
+	pub max_width: Value,
+	pub max_height: Value,
+	pub resizable: Value,
+}
 
-// impl Window {
-// 	pub fn construct(module: &Module, parse_tree: ParserElement) -> Box<dyn Element> {
-// 		let children = build_elements(module, parse_tree.children);
-// 		let (standard_props, title, _) = props(
-// 			parse_tree.properties,
-// 			Default::default(),
-// 			|props| Window::window_props(props));
-// 		Box::new(Window { children, standard_props, title })
-// 	}
+impl Window {
+	pub fn construct(scope: &mut Module, parse_tree: &ParserElement) -> ConstructedElementImpl {
+		ConstructedElementImpl::new(
+			Box::new(
+				Window {
+					title: Value::String("".to_owned()),
+					width: Value::Px(800.0),
+					height: Value::Px(600.0),
+					min_width: Value::Unset,
+					min_height: Value::Unset,
+					max_width: Value::Unset,
+					max_height: Value::Unset,
+					resizable: Value::Boolean(true),
+				}
+			),
+			build_elements(scope, &parse_tree.children),
+			Default::default(),
+		)
+	}
+}
 
-// 	fn window_props(props: &mut HashMap<&str, Value>) -> Value {
-// 		props.remove("title").unwrap_or_default()
-// 	}
-// }
+impl ElementImpl for Window {
+	fn property_types(&self) -> HashMap<String, Type> {
+		hashmap![
+			"title".into() => Type::String,
+			"width".into() => Type::Length,
+			"height".into() => Type::Length,
+			"min_width".into() => Type::Length,
+			"min_height".into() => Type::Length,
+			"max_width".into() => Type::Length,
+			"max_height".into() => Type::Length,
+			"resizable".into() => Type::Boolean,
+		]
+	}
+
+	fn set_property(&mut self, k: &String, v: &Value) -> SetPropertyResult {
+		match k.as_str() {
+			"title" => { self.title = v.clone() },
+			"width" => { self.width = v.clone() },
+			"height" => { self.height = v.clone() },
+			"min_width" => { self.min_width = v.clone() },
+			"min_height" => { self.min_height = v.clone() },
+			"max_width" => { self.max_width = v.clone() },
+			"max_height" => { self.max_height = v.clone() },
+			"resizable" => { self.resizable = v.clone() },
+			_ => { return SetPropertyResult::Ignore },
+		}
+		SetPropertyResult::Set
+	}
+}
 
 #[derive(Debug)]
 pub struct Rect {
@@ -566,9 +1211,17 @@ pub struct Rect {
 	pub background: Value,
 	pub border_width: Value,
 	pub border_color: Value,
+	pub radius: Value,
+	pub shadow_x: Value,
+	pub shadow_y: Value,
+	pub shadow_blur: Value,
+	pub shadow_color: Value,
+	pub opacity: Value,
 }
 
 impl ElementImpl for Rect {
+	fn relative_properties(&self) -> &'static [&'static str] { &["x", "y", "width", "height"] }
+
 	fn property_types(&self) -> HashMap<String, Type> {
 		hashmap![
 			"clip".into() => Type::Boolean,
@@ -581,6 +1234,14 @@ impl ElementImpl for Rect {
 				"width".into() => Type::Length,
 				"color".into() => Type::Brush,
 			]),
+			"radius".into() => Type::Length,
+			"shadow".into() => Type::Object(hashmap![
+				"x".into() => Type::Length,
+				"y".into() => Type::Length,
+				"blur".into() => Type::Length,
+				"color".into() => Type::Brush,
+			]),
+			"opacity".into() => Type::Float,
 		]
 	}
 
@@ -604,6 +1265,32 @@ impl ElementImpl for Rect {
 					_ => { return SetPropertyResult::TypeError }
 				}
 			},
+			"radius" => { self.radius = v.clone() },
+			"shadow" => {
+				match v {
+					Value::Object(map) => {
+						if let Some(x) = map.get(&"x".to_owned()) {
+							self.shadow_x = x.clone();
+						}
+						if let Some(y) = map.get(&"y".to_owned()) {
+							self.shadow_y = y.clone();
+						}
+						if let Some(blur) = map.get(&"blur".to_owned()) {
+							self.shadow_blur = blur.clone();
+						}
+						if let Some(color) = map.get(&"color".to_owned()) {
+							self.shadow_color = color.clone();
+						}
+					},
+					_ => { return SetPropertyResult::TypeError }
+				}
+			},
+			"opacity" => {
+				self.opacity = match v {
+					Value::Int(n) => Value::Float(*n as f32),
+					v => v.clone(),
+				};
+			},
 			"background" => { self.background = v.clone() },
 			_ => { return SetPropertyResult::Ignore },
 		}
@@ -622,6 +1309,12 @@ impl Rect {
 			border_width: Value::Px(0.0),
 			border_color: Value::Color(0,0,0,0.0),
 			background: Value::Color(0,0,0,0.0),
+			radius: Value::Px(0.0),
+			shadow_x: Value::Px(0.0),
+			shadow_y: Value::Px(0.0),
+			shadow_blur: Value::Px(0.0),
+			shadow_color: Value::Color(0,0,0,0.0),
+			opacity: Value::Float(1.0),
 		};
 		
 		ConstructedElementImpl::new(
@@ -645,6 +1338,8 @@ pub struct Scroll {
 	pub height: Value,
 	pub x: Value,
 	pub y: Value,
+	pub offset_x: Value,
+	pub offset_y: Value,
 }
 
 impl Scroll {
@@ -656,21 +1351,30 @@ impl Scroll {
 					height: Value::Px(0.0),
 					x: Value::Px(0.0),
 					y: Value::Px(0.0),
+					offset_x: Value::Unset,
+					offset_y: Value::Unset,
 				}
 			),
 			build_elements(scope, &parse_tree.children),
-			Default::default(),
+			EventsSpec {
+				scroll: true,
+				..Default::default()
+			},
 		)
 	}
 }
 
 impl ElementImpl for Scroll {
+	fn relative_properties(&self) -> &'static [&'static str] { &["x", "y", "width", "height"] }
+
 	fn property_types(&self) -> HashMap<String, Type> {
 		hashmap![
 			"width".into() => Type::Length,
 			"height".into() => Type::Length,
 			"x".into() => Type::Length,
 			"y".into() => Type::Length,
+			"offset_x".into() => Type::Length,
+			"offset_y".into() => Type::Length,
 		]
 	}
 
@@ -680,6 +1384,8 @@ impl ElementImpl for Scroll {
 			"height" => { self.height = v.clone() },
 			"x" => { self.x = v.clone() },
 			"y" => { self.y = v.clone() },
+			"offset_x" => { self.offset_x = v.clone() },
+			"offset_y" => { self.offset_y = v.clone() },
 			_ => { return SetPropertyResult::Ignore },
 		}
 		SetPropertyResult::Set
@@ -714,6 +1420,8 @@ impl Span {
 }
 
 impl ElementImpl for Span {
+	fn relative_properties(&self) -> &'static [&'static str] { &["x", "y"] }
+
 	fn property_types(&self) -> HashMap<String, Type> {
 		hashmap![
 			"color".into() => Type::Brush,
@@ -784,17 +1492,54 @@ impl ComponentInstance {
 			prop_decls: component.props.clone(),
 			properties: HashMap::new(),
 		};
+		let children = build_elements(scope, &parse_tree.children);
+		check_slots(scope, component, parse_tree, &children);
 		ConstructedElementImpl::new(
 			Box::new(data),
-			build_elements(scope, &parse_tree.children),
+			children,
 			Default::default(),
 		)
 	}
 }
 
+// elements passed to an instance end up in every slot whose filter accepts them; forwarded
+// `@children` have no name of their own, so only unfiltered slots accept them
+fn slot_accepts(slot: &Children, content: &Content) -> bool {
+	match (&slot.filter, content) {
+		(None, _) => true,
+		(Some(filter), Content::Element(e)) => filter.contains(&e.tag),
+		(Some(_), Content::Children(_)) => false,
+	}
+}
+
+fn check_slots(scope: &mut Module, component: &Component, parse_tree: &ParserElement, children: &[Content]) {
+	if children.is_empty() {
+		return;
+	}
+	if component.slots.is_empty() {
+		scope.error(parse_tree.span, format!("`{}` does not accept child elements", parse_tree.path.join(".")));
+		return;
+	}
+	for slot in component.slots.iter().filter(|e| e.single) {
+		let count = children.iter().filter(|e| slot_accepts(slot, e)).count();
+		if count > 1 {
+			let accepted = match &slot.filter {
+				Some(filter) => format!(" out of `{}`", filter.join("`, `")),
+				None => String::new(),
+			};
+			scope.error(parse_tree.span, format!(
+				"`{}` accepts a single child element{}, found {}",
+				parse_tree.path.join("."),
+				accepted,
+				count,
+			));
+		}
+	}
+}
+
 impl ElementImpl for ComponentInstance {
 	fn set_property(&mut self, k: &String, v: &Value) -> SetPropertyResult {
-		if self.prop_decls.contains_key(k) {
+		if self.prop_decls.get(k).map(|e| e.is_pub).unwrap_or(false) {
 			self.properties.insert(k.clone(), v.clone());
 			return SetPropertyResult::Set
 		}
@@ -804,6 +1549,7 @@ impl ElementImpl for ComponentInstance {
 	fn property_types(&self) -> HashMap<String, Type> {
 		self.prop_decls
 			.iter()
+			.filter(|(_, v)| v.is_pub)
 			.fold(
 				HashMap::new(),
 				|mut map, (k, v)| { map.insert(k.clone(), v.prop_type.clone()); map })
@@ -822,10 +1568,26 @@ impl LayoutItem {
 	fn set_property(&mut self, k: &String, v: &Value) -> SetPropertyResult {
 		match k.as_str() {
 			"align" => { self.align = v.clone(); SetPropertyResult::Set },
-			"stretch" if !self.grow => { self.stretch = v.clone(); SetPropertyResult::Set },
+			"stretch" if !self.grow => {
+				self.stretch = match v {
+					Value::Int(n) => Value::Float(*n as f32),
+					v => v.clone(),
+				};
+				SetPropertyResult::Set
+			},
 			_ => { SetPropertyResult::Ignore }
 		}
 	}
+
+	fn property_types(&self) -> HashMap<String, Type> {
+		let mut types = hashmap![
+			"align".into() => Type::Alignment,
+		];
+		if !self.grow {
+			types.insert("stretch".into(), Type::Float);
+		}
+		types
+	}
 }
 
 impl LayoutItem {
@@ -867,27 +1629,23 @@ impl Layout {
 		Self::construct(scope, parse_tree, true, true)
 	}
 	pub fn construct(scope: &mut Module, parse_tree: &ParserElement, column: bool, grow: bool) -> ConstructedElementImpl {
-		let (width, height) = match (column, grow) {
-			(false, false) => {
-				(Value::Px(0.0), Value::Unset)
-			},
-			(true, false) => {
-				(Value::Unset, Value::Px(0.0))
-			},
-			_ => {
-				(Value::Unset, Value::Unset)
-			}
-		};
+		// an unset width or height fits the content
 		let data = Layout {
 			rect: Rect {
 				clip: Value::Boolean(true),
 				x: Value::Px(0.0),
 				y: Value::Px(0.0),
-				width,
-				height,
+				width: Value::Unset,
+				height: Value::Unset,
 				border_width: Value::Px(0.0),
 				border_color: Value::Color(0,0,0,0.0),
 				background: Value::Color(0,0,0,0.0),
+				radius: Value::Px(0.0),
+				shadow_x: Value::Px(0.0),
+				shadow_y: Value::Px(0.0),
+				shadow_blur: Value::Px(0.0),
+				shadow_color: Value::Color(0,0,0,0.0),
+				opacity: Value::Float(1.0),
 			},
 			padding: Value::Px(0.0),
 			spacing: Value::Px(0.0),
@@ -914,8 +1672,12 @@ impl Layout {
 }
 
 impl ElementImpl for Layout {
+	fn relative_properties(&self) -> &'static [&'static str] { &["x", "y", "width", "height"] }
+
 	fn property_types(&self) -> HashMap<String, Type> {
 		let mut props = self.rect.property_types();
+		props.insert("padding".into(), Type::Length);
+		props.insert("spacing".into(), Type::Length);
 		match (self.column, self.grow) {
 			(false, true) => {
 				props.remove("width".into());
@@ -937,10 +1699,7 @@ impl ElementImpl for Layout {
 			("clip", ..) => { self.rect.clip = v.clone() },
 			("x", ..) => { self.rect.x = v.clone() },
 			("y", ..) => { self.rect.y = v.clone() },
-			("width", ..) => { self.rect.width = v.clone() },
-			("height", ..) => { self.rect.height = v.clone() },
-			("border_width", ..) => { self.rect.border_width = v.clone() },
-			("border_color", ..) => { self.rect.border_color = v.clone() },
+			("border" | "radius" | "shadow" | "opacity", ..) => { return self.rect.set_property(k, v) },
 			("background", ..) => { self.rect.background = v.clone() },
 			("padding", ..) => { self.padding = v.clone() }
 			("spacing", ..) => { self.spacing = v.clone() }
@@ -951,33 +1710,241 @@ impl ElementImpl for Layout {
 }
 
 
-// #[derive(Debug)]
-// pub struct Img {
-// 	pub standard_props: StandardProps,
-// 	pub src: Value,
-// }
+#[derive(Debug)]
+pub struct Img {
+	pub x: Value,
+	pub y: Value,
+	pub width: Value,
+	pub height: Value,
+	pub src: Value,
+	pub fit: Value,
+}
 
-// #[derive(Default)]
-// struct ImgProps {
-// 	pub src: Value,
-// }
+impl Img {
+	pub fn construct(scope: &mut Module, parse_tree: &ParserElement) -> ConstructedElementImpl {
+		ConstructedElementImpl::new(
+			Box::new(
+				Img {
+					x: Value::Px(0.0),
+					y: Value::Px(0.0),
+					width: Value::Px(0.0),
+					height: Value::Px(0.0),
+					src: Value::String("".to_owned()),
+					fit: Value::Fit(Fit::Fill),
+				}
+			),
+			build_elements(scope, &parse_tree.children),
+			EventsSpec {
+				pointer_click: true,
+				pointer_out: true,
+				pointer_in: true,
+				pointer_press: true,
+				pointer_release: true,
+				..Default::default()
+			},
+		)
+	}
+}
 
-// impl Img {
-// 	pub fn construct(_module: &Module, parse_tree: ParserElement) -> Box<dyn Element> {
-// 		let (standard_props, img_props, _) = props(
-// 			parse_tree.properties,
-// 			Default::default(),
-// 			|props| Self::get_props(props));
-		
-// 		Box::new(Img { standard_props, src: img_props.src })
-// 	}
+impl ElementImpl for Img {
+	fn relative_properties(&self) -> &'static [&'static str] { &["x", "y", "width", "height"] }
+
+	fn property_types(&self) -> HashMap<String, Type> {
+		hashmap![
+			"x".into() => Type::Length,
+			"y".into() => Type::Length,
+			"width".into() => Type::Length,
+			"height".into() => Type::Length,
+			"src".into() => Type::String,
+			"fit".into() => Type::Fit,
+		]
+	}
 
-// 	fn get_props(props: &mut HashMap<&str, Value>) -> ImgProps {
-// 		let mut img_props = ImgProps::default();
-// 		img_props.src = props.remove(&"src").unwrap_or_default();
-// 		img_props
-// 	}
-// }
+	fn set_property(&mut self, k: &String, v: &Value) -> SetPropertyResult {
+		match k.as_str() {
+			"x" => { self.x = v.clone() },
+			"y" => { self.y = v.clone() },
+			"width" => { self.width = v.clone() },
+			"height" => { self.height = v.clone() },
+			"src" => { self.src = v.clone() },
+			"fit" => { self.fit = v.clone() },
+			_ => { return SetPropertyResult::Ignore },
+		}
+		SetPropertyResult::Set
+	}
+}
 
 
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::super::parser;
+	use std::path::{Path, PathBuf};
 
+	// the messages of every error and warning checking the component `source` reports
+	fn diagnostics(source: &str) -> Vec<String> {
+		diagnostics_importing(source, &[])
+	}
+
+	// like `diagnostics`, with the components in `imports` available by their names
+	fn diagnostics_importing(source: &str, imports: &[(&str, &str)]) -> Vec<String> {
+		let mut imports_map = HashMap::new();
+		let mut components = HashMap::new();
+		for (name, source) in imports {
+			let path = PathBuf::from(format!("{name}.ui"));
+			let mut parse_tree = parser::parse(source, &path).unwrap();
+			parse_tree.name = name.to_string();
+			let (imports, components_so_far) = (HashMap::new(), HashMap::new());
+			let mut module = Module::new(&path, &imports, &components_so_far, &parse_tree.props);
+			let component = build_component(&mut module, &parse_tree);
+			imports_map.insert(name.to_string(), path.clone());
+			components.insert(path, component);
+		}
+		let path = Path::new("test.ui");
+		let parse_tree = parser::parse(source, path).unwrap();
+		let mut module = Module::new(path, &imports_map, &components, &parse_tree.props);
+		build_component(&mut module, &parse_tree);
+		module.diagnostics.into_iter().map(|d| d.message).collect()
+	}
+
+	fn lit(value: Value) -> Expr {
+		Expr::Literal(Box::new(value))
+	}
+
+	fn prop(name: &str) -> Expr {
+		Expr::Path(vec![name.to_string()], Ctx::Component)
+	}
+
+	fn binary(op: BinaryOp, lhs: Expr, rhs: Expr) -> Expr {
+		Expr::Binary(op, Box::new(lhs), Box::new(rhs))
+	}
+
+	fn call(name: &str, args: Vec<Expr>) -> Expr {
+		Expr::Call(name.to_string(), args)
+	}
+
+	fn is_cast(expr: &Expr, to: Type) -> bool {
+		matches!(expr, Expr::Cast(_, t) if *t == to)
+	}
+
+	// checks `expr` with the props `n: Int`, `f: Float`, `s: String`, `w: Length` and
+	// `items: [String]`, returning its type, the expression as checking rewrote it and the errors
+	fn check(mut expr: Expr) -> (Option<Type>, Expr, Vec<String>) {
+		let props: HashMap<_, _> = [
+			("n", Type::Int),
+			("f", Type::Float),
+			("s", Type::String),
+			("w", Type::Length),
+			("items", Type::Iter(Box::new(Type::String))),
+		].into_iter().map(|(name, prop_type)| {
+			let decl = PropDecl { is_pub: true, name: name.to_string(), prop_type, default: None, span: SourceSpan::new(0, 0) };
+			(name.to_string(), decl)
+		}).collect();
+		let (imports, components) = (HashMap::new(), HashMap::new());
+		let mut module = Module::new(Path::new("test.ui"), &imports, &components, &props);
+		let t = check_expr(&mut module, SourceSpan::new(0, 0), &mut expr);
+		(t, expr, module.diagnostics.into_iter().map(|d| d.message).collect())
+	}
+
+	#[test]
+	fn numbers_mix_as_floats() {
+		let (t, _, errors) = check(binary(BinaryOp::Add, prop("n"), lit(Value::Int(1))));
+		assert_eq!((t, errors), (Some(Type::Int), vec![]));
+		let (t, e, _) = check(binary(BinaryOp::Mul, prop("n"), prop("f")));
+		assert_eq!(t, Some(Type::Float));
+		assert!(matches!(e, Expr::Binary(BinaryOp::Mul, lhs, _) if is_cast(&lhs, Type::Float)));
+		// an Int literal is made a Float literal rather than cast
+		let (t, e, _) = check(binary(BinaryOp::Rem, prop("f"), lit(Value::Int(2))));
+		assert_eq!(t, Some(Type::Float));
+		let Expr::Binary(BinaryOp::Rem, _, rhs) = e else { unreachable!() };
+		assert!(matches!(*rhs, Expr::Literal(v) if matches!(*v, Value::Float(n) if n == 2.0)));
+		let (_, _, errors) = check(binary(BinaryOp::Sub, prop("n"), lit(Value::Boolean(true))));
+		assert_eq!(errors, vec!["operator `-` cannot be applied to types Int and Boolean"]);
+	}
+
+	#[test]
+	fn int_division_is_checked() {
+		let (t, e, _) = check(binary(BinaryOp::Div, prop("n"), lit(Value::Int(0))));
+		assert_eq!(t, Some(Type::Int));
+		assert!(matches!(e, Expr::Binary(BinaryOp::IntDiv, ..)));
+		let (_, e, _) = check(binary(BinaryOp::Rem, prop("n"), prop("n")));
+		assert!(matches!(e, Expr::Binary(BinaryOp::IntRem, ..)));
+		let (_, e, _) = check(binary(BinaryOp::Div, prop("n"), prop("f")));
+		assert!(matches!(e, Expr::Binary(BinaryOp::Div, ..)));
+	}
+
+	#[test]
+	fn strings_concatenate_with_numbers_and_booleans() {
+		let (t, e, errors) = check(binary(BinaryOp::Add, prop("s"), prop("n")));
+		assert_eq!((t, errors), (Some(Type::String), vec![]));
+		assert!(matches!(e, Expr::Binary(BinaryOp::Concat, _, rhs) if is_cast(&rhs, Type::String)));
+		let (t, _, _) = check(binary(BinaryOp::Add, lit(Value::Boolean(true)), prop("s")));
+		assert_eq!(t, Some(Type::String));
+		let (_, _, errors) = check(binary(BinaryOp::Add, prop("s"), prop("w")));
+		assert_eq!(errors, vec!["cannot concatenate value of type Length with a String"]);
+		let (_, _, errors) = check(binary(BinaryOp::Sub, prop("s"), prop("s")));
+		assert_eq!(errors, vec!["operator `-` cannot be applied to types String and String"]);
+	}
+
+	#[test]
+	fn lengths_add_and_scale() {
+		let (t, _, errors) = check(binary(BinaryOp::Sub, lit(Value::Length(50.0, Unit::Percent)), prop("w")));
+		assert_eq!((t, errors), (Some(Type::Length), vec![]));
+		let (t, e, _) = check(binary(BinaryOp::Mul, prop("n"), prop("w")));
+		assert_eq!(t, Some(Type::Length));
+		assert!(matches!(e, Expr::Binary(BinaryOp::Mul, lhs, _) if is_cast(&lhs, Type::Float)));
+		let (t, _, _) = check(binary(BinaryOp::Div, prop("w"), prop("f")));
+		assert_eq!(t, Some(Type::Length));
+		let (_, _, errors) = check(binary(BinaryOp::Div, prop("n"), prop("w")));
+		assert_eq!(errors, vec!["operator `/` cannot be applied to types Int and Length"]);
+		let (_, _, errors) = check(binary(BinaryOp::Add, prop("w"), prop("n")));
+		assert_eq!(errors, vec!["operator `+` cannot be applied to types Length and Int"]);
+	}
+
+	#[test]
+	fn only_absolute_lengths_compare() {
+		let (t, _, errors) = check(binary(BinaryOp::Lt, prop("w"), lit(Value::Px(10.0))));
+		assert_eq!((t, errors), (Some(Type::Boolean), vec![]));
+		let (_, _, errors) = check(binary(BinaryOp::Ge, prop("w"), lit(Value::Length(50.0, Unit::Percent))));
+		assert_eq!(errors, vec!["relative lengths can't be compared, only lengths in absolute units"]);
+	}
+
+	#[test]
+	fn checks_calls() {
+		let (t, _, errors) = check(call("len", vec![prop("items")]));
+		assert_eq!((t, errors), (Some(Type::Int), vec![]));
+		let (t, e, _) = check(call("max", vec![prop("n"), prop("f")]));
+		assert_eq!(t, Some(Type::Float));
+		assert!(matches!(&e, Expr::Call(_, args) if is_cast(&args[0], Type::Float) && !is_cast(&args[1], Type::Float)));
+		let (t, e, _) = check(call("round", vec![prop("n")]));
+		assert_eq!(t, Some(Type::Int));
+		assert!(matches!(&e, Expr::Call(_, args) if is_cast(&args[0], Type::Float)));
+		let (_, _, errors) = check(call("len", vec![prop("s")]));
+		assert_eq!(errors, vec!["invalid argument type(s) for function `len`: [String]"]);
+		let (_, _, errors) = check(call("clamp", vec![prop("n")]));
+		assert_eq!(errors, vec!["function `clamp` takes 3 argument(s), but 1 were supplied"]);
+		let (_, _, errors) = check(call("pow", vec![prop("n")]));
+		assert_eq!(errors, vec!["call to unknown function `pow`"]);
+	}
+
+	#[test]
+	fn grow_layouts_have_no_main_axis_size() {
+		assert_eq!(diagnostics("row.grow { width: 10px; }"), vec!["tried to set nonexistent property `width`"]);
+		assert_eq!(diagnostics("column.grow { height: 10px; }"), vec!["tried to set nonexistent property `height`"]);
+		assert!(diagnostics("row.grow { height: 10px; } ").is_empty());
+		assert!(diagnostics("column.stretch { width: 10px; height: 10px; }").is_empty());
+	}
+
+	#[test]
+	fn slots_limit_the_children_they_accept() {
+		// the diagnostics of giving `children` to an instance of `component`
+		let give = |component: &str, children: &str| {
+			diagnostics_importing(&format!("import \"card.ui\";\n\ncard {{ {children} }}"), &[("card", component)])
+		};
+		let card = "rect {\n\trect { @child(span) }\n\trect { @children }\n}";
+		assert!(give(card, "span { \"a\" } rect { } rect { }").is_empty());
+		assert_eq!(give(card, "span { \"a\" } span { \"b\" }"), vec!["`card` accepts a single child element out of `span`, found 2"]);
+		assert_eq!(give("rect { @child }", "rect { } rect { }"), vec!["`card` accepts a single child element, found 2"]);
+		assert_eq!(give("rect { }", "rect { }"), vec!["`card` does not accept child elements"]);
+	}
+}
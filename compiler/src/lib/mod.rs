@@ -7,11 +7,16 @@ use std::collections::HashMap;
 mod parser;
 mod elements;
 mod codegen;
+mod colors;
+mod diagnostic;
 
 use elements as el;
 use elements::{Component, ComponentInstance};
+use diagnostic::{Diagnostic, Span};
 
 pub struct Module<'a> {
+	path: &'a Path,
+	diagnostics: Vec<Diagnostic>,
 	builtins: HashMap<String, Item>,
 	imports: &'a HashMap<String, PathBuf>,
 	props: &'a HashMap<String, PropDecl>,
@@ -36,21 +41,25 @@ pub struct PropDecl {
 	pub name: String,
 	pub prop_type: Type,
 	pub default: Option<Value>,
+	pub span: Span,
 }
 
 impl <'a> Module<'a> {
 	pub fn new(
+		path: &'a Path,
 		imports: &'a HashMap<String, PathBuf>,
 		components: &'a HashMap<PathBuf, Component>,
 		props: &'a HashMap<String, PropDecl>,
 	) -> Self {
 		Self {
+			path,
+			diagnostics: Vec::new(),
 			imports,
 			props,
 			components,
 			stack: Vec::new(),
 			builtins: hashmap![
-				// String::from("window") => Item::Constructor(el::Window::construct),
+				String::from("window") => Item::Constructor(el::Window::construct),
 				String::from("rect")   => Item::Constructor(el::Rect::construct),
 				String::from("text")   => Item::Constructor(el::Text::construct),
 				String::from("span")   => Item::Constructor(el::Span::construct),
@@ -63,52 +72,64 @@ impl <'a> Module<'a> {
 					String::from("grow") => Item::Constructor(el::Layout::column_grow),
 					String::from("stretch") => Item::Constructor(el::Layout::column_stretch),
 				]),
-				// String::from("img")    => Item::Constructor(el::Img::construct),
+				String::from("img")    => Item::Constructor(el::Img::construct),
 			]
 		}
 	}
 
+	// reports an element that can't be found, in which case there is nothing to construct
 	pub fn construct(
 		&mut self,
 		parse_tree: &parser::Element,
-	) -> Result<el::ConstructedElementImpl, String> {
+	) -> Option<el::ConstructedElementImpl> {
 		assert!(parse_tree.path.len() > 0);
 
 		if parse_tree.path.len() == 1 {
 			if let Some(file_path) = self.imports.get(&parse_tree.path[0]) {
 				let component = self.components.get(file_path).unwrap();
-				return Ok(ComponentInstance::construct(self, &component, parse_tree));
+				return Some(ComponentInstance::construct(self, &component, parse_tree));
 			}
 		}
 
-		Ok(self.lookup(&parse_tree.path)?(self, parse_tree))
+		let constructor = self.lookup(parse_tree.span, &parse_tree.path)?;
+		Some(constructor(self, parse_tree))
+	}
+
+	pub fn error<S: Into<String>>(&mut self, span: Span, message: S) {
+		self.diagnostics.push(Diagnostic::error(self.path, span, message));
+	}
+
+	pub fn warning<S: Into<String>>(&mut self, span: Span, message: S) {
+		self.diagnostics.push(Diagnostic::warning(self.path, span, message));
 	}
 	
-	fn lookup(&self, path: &Vec<String>) -> Result<el::Constructor, String> {
+	fn lookup(&mut self, span: Span, path: &[String]) -> Option<el::Constructor> {
 		let mut map = &self.builtins;
 		let mut it = path.iter().peekable();
-		loop {
+		let message = loop {
 			let segment = it.next().unwrap();
 
 			if let Some(item) = map.get(segment) {
 				match item {
 					Item::Module(next_map) => {
 						if it.peek().is_none() {
-							return Err(format!("'{}' is a module, not an element", segment))
+							break format!("'{}' is a module, not an element", segment);
 						}
 						map = next_map;
 					},
 					Item::Constructor(constructor) => {
 						if it.peek().is_some() {
-							return Err(format!("'{}' is an element, not a module", segment))
+							break format!("'{}' is an element, not a module", segment);
 						}
-						return Ok(*constructor);
+						return Some(*constructor);
 					},
 				}
 			} else {
-				return Err(format!("item '{}' not found", segment))
+				break format!("item '{}' not found", segment);
 			}
-		}
+		};
+		self.error(span, message);
+		None
 	}
 }
 
@@ -143,9 +164,75 @@ pub enum Ctx {
 	Parent,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnaryOp {
+	Not,
+	Neg,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOp {
+	Or,
+	And,
+	Eq,
+	Ne,
+	Lt,
+	Le,
+	Gt,
+	Ge,
+	Add,
+	Sub,
+	Mul,
+	Div,
+	Rem,
+	Concat,
+	// integer division, which has no result for a zero divisor
+	IntDiv,
+	IntRem,
+}
+
+impl UnaryOp {
+	pub fn symbol(&self) -> &'static str {
+		match self {
+			UnaryOp::Not => "!",
+			UnaryOp::Neg => "-",
+		}
+	}
+}
+
+impl BinaryOp {
+	pub fn symbol(&self) -> &'static str {
+		match self {
+			BinaryOp::Or     => "||",
+			BinaryOp::And    => "&&",
+			BinaryOp::Eq     => "==",
+			BinaryOp::Ne     => "!=",
+			BinaryOp::Lt     => "<",
+			BinaryOp::Le     => "<=",
+			BinaryOp::Gt     => ">",
+			BinaryOp::Ge     => ">=",
+			BinaryOp::Add    => "+",
+			BinaryOp::Sub    => "-",
+			BinaryOp::Mul    => "*",
+			BinaryOp::Div    => "/",
+			BinaryOp::Rem    => "%",
+			BinaryOp::Concat => "+",
+			BinaryOp::IntDiv => "/",
+			BinaryOp::IntRem => "%",
+		}
+	}
+}
+
 #[derive(Debug, Clone)]
 pub enum Expr {
 	Path(Vec<String>, Ctx),
+	Literal(Box<Value>),
+	Unary(UnaryOp, Box<Expr>),
+	Binary(BinaryOp, Box<Expr>, Box<Expr>),
+	Ternary(Box<Expr>, Box<Expr>, Box<Expr>),
+	Call(String, Vec<Expr>),
+	// inserted by the type checker wherever an operand needs an implicit conversion
+	Cast(Box<Expr>, Type),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -158,6 +245,7 @@ pub enum Type {
 	String,
 	Boolean,
 	Alignment,
+	Fit,
 	Callback,
 	Iter(Box<Type>),
 	Object(HashMap<String, Type>),
@@ -171,20 +259,74 @@ pub enum Alignment {
 	End,
 }
 
+// how an image is scaled into its bounds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fit {
+	Contain,
+	Cover,
+	Fill,
+}
+
+// the units of a length other than `px`; the names match the variants of `ui::Length`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+	In,
+	Cm,
+	Mm,
+	Pt,
+	Percent,
+	Em,
+	Vw,
+	Vh,
+}
+
+// `linear(90deg, #f00, #00f 80%)` or `radial(#fff, #000)`
+#[derive(Debug, Clone)]
+pub enum Gradient {
+	Linear(f32, Vec<ColorStop>),
+	Radial(Vec<ColorStop>),
+}
+
+// the position is a fraction of the gradient line
+#[derive(Debug, Clone)]
+pub struct ColorStop {
+	pub color: (u8, u8, u8, f32),
+	pub position: Option<f32>,
+}
+
 #[derive(Debug, Clone)]
 pub enum Value {
 	Px(f32),
+	Length(f32, Unit),
 	Float(f32),
 	Int(i32),
 	Color(u8, u8, u8, f32),
+	Gradient(Gradient),
 	String(String),
 	Boolean(bool),
-	Binding(Expr),
+	Binding(Expr, Span),
 	Alignment(Alignment),
+	Fit(Fit),
 	Object(HashMap<String, Value>),
 	Unset,
 }
 
+// what a repeater iterates over
+#[derive(Debug, Clone)]
+pub enum Collection {
+	// a bound list, or an Int to count up to
+	Value(Value),
+	// `0..(count)` or `1..=5 step 2`
+	Range {
+		start: Value,
+		end: Value,
+		inclusive: bool,
+		step: Option<Value>,
+	},
+	// `["A", "B", "C"]`
+	Array(Vec<Value>),
+}
+
 impl Value {
 	fn is_set(&self) -> bool {
 		if let Value::Unset = self {
@@ -215,58 +357,70 @@ fn hex_to_int(hex: u8) -> u8 {
 }
 
 impl Value {
+	// `rgb`, `rgba`, `rrggbb` or `rrggbbaa`
 	pub fn color_from_hex(hex: &str) -> Value {
 		let hex = hex.as_bytes();
-		assert!(hex.len() == 3 || hex.len() == 6);
-		match hex.len() {
-			3 => {
-				let mut r = hex_to_int(hex[0]);
-				let mut g = hex_to_int(hex[1]);
-				let mut b = hex_to_int(hex[2]);
-				r = (r << 4) + r;
-				g = (g << 4) + g;
-				b = (b << 4) + b;
-				Value::Color(r, g, b, 1.0)
-			},
-			6 => {
-				let r = (hex_to_int(hex[0]) << 4) + hex_to_int(hex[1]);
-				let g = (hex_to_int(hex[2]) << 4) + hex_to_int(hex[3]);
-				let b = (hex_to_int(hex[4]) << 4) + hex_to_int(hex[5]);
-				Value::Color(r, g, b, 1.0)
-			},
-			_ => unreachable!(),
+		assert!(matches!(hex.len(), 3 | 4 | 6 | 8));
+		let channels: Vec<u8> = if hex.len() <= 4 {
+			hex.iter().map(|&c| hex_to_int(c) * 17).collect()
+		} else {
+			hex.chunks(2).map(|c| (hex_to_int(c[0]) << 4) + hex_to_int(c[1])).collect()
+		};
+		let a = channels.get(3).map(|&a| a as f32 / 255.0).unwrap_or(1.0);
+		Value::Color(channels[0], channels[1], channels[2], a)
+	}
+}
+
+use std::{ fs, process, path::{Path, PathBuf} };
+
+#[derive(Debug)]
+pub enum Error {
+	Io(PathBuf, std::io::Error),
+	InvalidImport(PathBuf),
+	RecursiveImport(PathBuf),
+	// rendered diagnostics for every error found in a file
+	Compile(String),
+}
+
+impl std::fmt::Display for Error {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		match self {
+			Error::Io(path, e) => write!(f, "error: {}: {}", path.display(), e),
+			Error::InvalidImport(path) => write!(f, "error: invalid path specified: {}", path.display()),
+			Error::RecursiveImport(path) => write!(f, "error: encountered recursive import: {}", path.display()),
+			Error::Compile(message) => write!(f, "{}", message),
 		}
 	}
 }
 
-use std::{ fs, process, path::PathBuf, io::Read };
+impl std::error::Error for Error {}
 
-fn load_single_ui_component<'a>(exe: &str, path: PathBuf) -> Result<parser::Component, String> {
-		let mut ui_string = String::new();
-		fs::File::open(&path)
-			.unwrap()
-			.read_to_string(&mut ui_string)
-			.unwrap();
+fn load_single_ui_component(path: PathBuf) -> Result<parser::Component, Error> {
+		let ui_string = fs::read_to_string(&path).map_err(|e| Error::Io(path.clone(), e))?;
 		
-		let component = if let Ok(mut component) = parser::parse(&ui_string) {
-			component.name = path
-				.file_stem()
-				.unwrap()
-				.to_string_lossy()
-				.into();
-			component
-		} else {
-			return Err(format!("{}: parse error", exe));
+		let mut component = match parser::parse(&ui_string, &path) {
+			Ok(component) => component,
+			Err(diagnostics) => {
+				let message = diagnostic::emit(&diagnostics, &ui_string).err().unwrap_or_default();
+				return Err(Error::Compile(message));
+			},
 		};
+		component.name = path
+			.file_stem()
+			.unwrap()
+			.to_string_lossy()
+			.into();
 
 		Ok(component)
 }
 
-fn resolve_ui_import<'a>(
-	exe: &str,
+// `loading` holds the files whose imports are being resolved, so that a file importing itself,
+// directly or not, is reported instead of recursing forever
+fn resolve_ui_import(
 	import: Import,
-	components: &'a mut HashMap<PathBuf, parser::Component>)
-	-> Result<(String, PathBuf), String> {
+	components: &mut HashMap<PathBuf, parser::Component>,
+	loading: &mut Vec<PathBuf>)
+	-> Result<(String, PathBuf), Error> {
 	
 	let pathbuf = if let Ok(path) = fs::canonicalize(&import.path) {
 		Some(path)
@@ -275,16 +429,19 @@ fn resolve_ui_import<'a>(
 	} else {
 		None
 	};
-	let pathbuf = if pathbuf.is_none() || !pathbuf.as_ref().unwrap().is_file() {
-		return Err(format!("{}: invalid path specified: {}", exe, import.path.display()));
-	} else {
-		pathbuf.unwrap()
+	let pathbuf = match pathbuf {
+		Some(pathbuf) if pathbuf.is_file() => pathbuf,
+		_ => return Err(Error::InvalidImport(import.path)),
 	};
 	if let Some(component) = components.get(&pathbuf) {
 		return Ok((component.name.clone(), pathbuf));
 	}
+	if loading.contains(&pathbuf) {
+		return Err(Error::RecursiveImport(pathbuf));
+	}
 
-	let mut component = load_single_ui_component(exe, pathbuf.clone())?;
+	let mut component = load_single_ui_component(pathbuf.clone())?;
+	loading.push(pathbuf.clone());
 	
 	while let Some(mut import) = component.import_decls.pop() {
 		let alias = import.alias.clone();
@@ -293,23 +450,23 @@ fn resolve_ui_import<'a>(
 			pathbuf.push(import.path);
 			import.path = pathbuf;
 		}
-		let (name, path) = resolve_ui_import(exe, import, components)?;
+		let (name, path) = resolve_ui_import(import, components, loading)?;
 		component.imports_map.insert(alias.unwrap_or(name), path);
 	}
+	loading.pop();
 	
 	let name = component.name.clone();
 	components.insert(pathbuf.clone(), component);
 	Ok((name, pathbuf))
 }
 
-fn load_ui_component<'a>(
-	exe: &str,
+fn load_ui_component(
 	path: &str,
 	parse_trees: &mut HashMap<PathBuf,
-	parser::Component>) -> Result<PathBuf, String> {
+	parser::Component>) -> Result<PathBuf, Error> {
 	
 	let ui_import = Import { path: path.into(), alias: None };
-	let (_, path) = resolve_ui_import(exe, ui_import, parse_trees)?;
+	let (_, path) = resolve_ui_import(ui_import, parse_trees, &mut Vec::new())?;
 	Ok(path)
 }
 
@@ -317,59 +474,84 @@ fn build_impl<'a>(
 	path: &PathBuf,
 	parse_trees: &HashMap<PathBuf, parser::Component>,
 	components: &'a mut HashMap<PathBuf, Component>,
+	is_root: bool,
 	web: bool,
-) -> Result<&'a Component, String> {
+) -> Result<&'a Component, Error> {
 	use parser::CompileStatus;
 	
 	let parse_tree = parse_trees.get(path).unwrap();
 	match parse_tree.status.get() {
 		CompileStatus::Ready => {},
-		CompileStatus::Building => { return Err(String::from("encountered recursive import")); },
+		CompileStatus::Building => { return Err(Error::RecursiveImport(path.clone())); },
 		CompileStatus::Done => { return Ok(components.get(path).unwrap()); },
 	}
 	parse_tree.status.set(CompileStatus::Building);
 	
 	for (_, path) in parse_tree.imports_map.iter() {
-		build_impl(path, parse_trees, components, web)?;
+		build_impl(path, parse_trees, components, false, web)?;
 	}
 
-	let mut module = Module::new(&parse_tree.imports_map, components, &parse_tree.props);
+	let mut module = Module::new(path, &parse_tree.imports_map, components, &parse_tree.props);
 	let component = el::build_component(&mut module, parse_tree);
+	let diagnostics: Vec<_> = parse_tree.diagnostics.iter().cloned().chain(module.diagnostics).collect();
+	diagnostic::emit(&diagnostics, &parse_tree.source).map_err(Error::Compile)?;
 	// println!("{:#?}", component);
 
-	let mut dir = path.parent().unwrap().to_path_buf();
-	dir.push("dist");
+	let dir = codegen::dist_dir(path);
 
 	let script_path = path.with_extension("js");
 	let script = if script_path.is_file() {
-		let mut script = String::new();
-		fs::File::open(&script_path)
-			.expect(&format!("failed to open file: {}", script_path.display()))
-			.read_to_string(&mut script).unwrap();
-		Some(script)
+		Some(fs::read_to_string(&script_path).map_err(|e| Error::Io(script_path.clone(), e))?)
 	} else {
 		None
 	};
 
-	codegen::generate(&component, script, &parse_tree.name, dir, web);
+	// everything besides the root was loaded as one of its (transitive) imports
+	let mut imports: Vec<_> = parse_trees
+		.iter()
+		.filter(|(import_path, _)| *import_path != path)
+		.map(|(import_path, e)| (e.name.clone(), import_path.clone()))
+		.collect();
+	imports.sort();
+	let imports = if is_root { Some(imports.as_slice()) } else { None };
+
+	let code = match codegen::generate(&component, imports, script, &parse_tree.name, path, web) {
+		Ok(code) => code,
+		Err(diagnostics) => {
+			let message = diagnostic::emit(&diagnostics, &parse_tree.source).err().unwrap_or_default();
+			return Err(Error::Compile(message));
+		},
+	};
+	codegen::write(&dir, &parse_tree.name, &code).map_err(|e| Error::Io(dir.clone(), e))?;
 
 	parse_tree.status.set(CompileStatus::Done);
 	components.insert(path.clone(), component);
 	Ok(components.get(path).unwrap())
 }
 
-pub fn build(exe: &str, path: &str, web: bool) -> Result<Vec<PathBuf>, String> {
+pub fn build_ui(path: &str, web: bool) -> Result<Vec<PathBuf>, Error> {
 	let mut parse_trees = HashMap::new();
-	let path = load_ui_component(&exe, &path, &mut parse_trees)?;
+	let path = load_ui_component(&path, &mut parse_trees)?;
 	// println!("{:#?}", component);
 
 	let mut components = HashMap::new();
-	build_impl(&path, &parse_trees, &mut components, web)?;
+	build_impl(&path, &parse_trees, &mut components, true, web)?;
 
 	Ok(components.into_iter().map(|(k,_)|k).collect())
 }
 
-pub fn watch(exe: &str, path: &str, web: bool) {
+// kept for build scripts written before errors were typed; `exe` no longer prefixes messages
+#[deprecated(note = "use `build_ui`, which returns a typed `Error`")]
+pub fn build(_exe: &str, path: &str, web: bool) -> Result<Vec<PathBuf>, String> {
+	build_ui(path, web).map_err(|e| e.to_string())
+}
+
+#[deprecated(note = "use `watch_ui`")]
+pub fn watch(_exe: &str, path: &str, web: bool) {
+	watch_ui(path, web)
+}
+
+pub fn watch_ui(path: &str, web: bool) {
 	use notify::{Watcher, RecursiveMode, DebouncedEvent, watcher};
 	use std::sync::mpsc::channel;
 	use std::time::Duration;
@@ -379,7 +561,7 @@ pub fn watch(exe: &str, path: &str, web: bool) {
 	let mut prev_paths = Vec::new();
 
 	let mut build_once = || {
-		match build(exe, path, web) {
+		match build_ui(path, web) {
 			Ok(paths) => {
 				for path in prev_paths.iter() {
 					watcher.unwatch(path).unwrap();
@@ -1,10 +1,11 @@
 use std::collections::HashMap;
-use std::cell::Cell;
-use std::path::PathBuf;
+use std::cell::{Cell, RefCell};
+use std::path::{Path, PathBuf};
 use maplit::hashmap;
+use nom_locate::LocatedSpan;
 
 use nom::{
-	IResult,
+	InputTake,
 	character::{
 		is_alphanumeric,
 		is_alphabetic,
@@ -26,6 +27,7 @@ use nom::{
 	multi::{
 		many0,
 		many1,
+		separated_list0,
 		separated_list1,
 		many1_count,
 	},
@@ -38,14 +40,13 @@ use nom::{
 		tuple,
 	},
 	combinator::{
-		eof,
 		map,
+		map_opt,
 		not,
 		opt,
 		peek,
 		recognize,
 		verify,
-		fail,
 	},
 	branch::{
 		alt,
@@ -54,43 +55,263 @@ use nom::{
 
 use super::{
 	Alignment,
+	Fit,
+	Gradient,
+	ColorStop,
+	Unit,
 	Value,
+	Collection,
 	Expr,
+	UnaryOp,
+	BinaryOp,
 	Import,
 	Ctx,
 	Type,
 	PropDecl,
+	colors,
+	diagnostic::{Diagnostic, Span},
 };
 
-type ParseError<'a> = nom::Err<nom::error::Error<&'a str>>;
+pub struct ParseState {
+	path: PathBuf,
+	diagnostics: RefCell<Vec<Diagnostic>>,
+}
 
-pub fn parse(input: &str) -> Result<Component, ParseError> {
-	let (_, (imports, props, root)) = tuple((
-		many0(delimited(skip_space, import, skip_space)),
-		delimited(skip_space, props, skip_space),
-		terminated(delimited(skip_space, element, skip_space), eof),
-	))
-	(input)?;
+impl ParseState {
+	fn error<S: Into<String>>(&self, span: Span, message: S) {
+		self.diagnostics.borrow_mut().push(Diagnostic::error(self.path.clone(), span, message));
+	}
 
-	if root.condition.is_some() || root.repeater.is_some() {
-		fail::<_, &str, _>(input)?;
+	fn syntax_error(&self, e: SyntaxError) {
+		let start = e.input.location_offset();
+		let found = match token(e.input) {
+			Some(token) => format!("`{}`", token),
+			None => String::from("end of file"),
+		};
+		let end = start + token(e.input).map(|t| t.len()).unwrap_or(0);
+		let message = match e.expected.as_slice() {
+			[] => format!("unexpected {}", found),
+			[expected] => format!("expected {}, found {}", expected, found),
+			expected => format!("expected one of {}, found {}", expected.join(", "), found),
+		};
+		self.error(Span::new(start, end), message);
 	}
+}
 
-	Ok(Component {
-		name: String::new(),
-		props,
-		root,
-		import_decls: imports,
-		imports_map: HashMap::new(),
-		status: Cell::new(CompileStatus::Ready),
-	})
+type Input<'a> = LocatedSpan<&'a str, &'a ParseState>;
+type IResult<'a, O> = nom::IResult<Input<'a>, O, SyntaxError<'a>>;
+
+// keeps whichever alternative got furthest into the input, so that errors point
+// at the token that actually broke the statement rather than at its first token
+pub struct SyntaxError<'a> {
+	input: Input<'a>,
+	expected: Vec<String>,
+}
+
+impl<'a> nom::error::ParseError<Input<'a>> for SyntaxError<'a> {
+	fn from_error_kind(input: Input<'a>, _kind: nom::error::ErrorKind) -> Self {
+		SyntaxError { input, expected: Vec::new() }
+	}
+
+	fn append(_input: Input<'a>, _kind: nom::error::ErrorKind, other: Self) -> Self {
+		other
+	}
+
+	fn from_char(input: Input<'a>, c: char) -> Self {
+		SyntaxError { input, expected: vec![format!("`{}`", c)] }
+	}
+
+	fn or(mut self, other: Self) -> Self {
+		use std::cmp::Ordering;
+		match self.input.location_offset().cmp(&other.input.location_offset()) {
+			Ordering::Greater => self,
+			Ordering::Less => other,
+			Ordering::Equal => {
+				for c in other.expected {
+					if !self.expected.contains(&c) {
+						self.expected.push(c);
+					}
+				}
+				self
+			},
+		}
+	}
+}
+
+// replaces the expectations of `parser` with `name` when it fails without consuming anything
+fn expected<'a, O, F>(name: &'static str, mut parser: F) -> impl FnMut(Input<'a>) -> IResult<'a, O>
+where
+	F: FnMut(Input<'a>) -> IResult<'a, O>,
+{
+	move |input: Input<'a>| {
+		parser(input).map_err(|e| match e {
+			nom::Err::Error(mut e) if e.input.location_offset() == input.location_offset() => {
+				e.expected = vec![String::from(name)];
+				nom::Err::Error(e)
+			},
+			e => e,
+		})
+	}
+}
+
+fn token<'a>(input: Input<'a>) -> Option<&'a str> {
+	let fragment: &'a str = input.fragment();
+	let c = fragment.chars().next()?;
+	if c.is_alphanumeric() || c == '_' {
+		fragment.split(|c: char| !c.is_alphanumeric() && c != '_').next()
+	} else {
+		Some(&fragment[..c.len_utf8()])
+	}
+}
+
+// runs `parser`, folding the two kinds of nom errors together; only `int` fails outright
+fn attempt<'a, O, F>(mut parser: F, input: Input<'a>) -> Result<(Input<'a>, O), SyntaxError<'a>>
+where
+	F: FnMut(Input<'a>) -> IResult<'a, O>,
+{
+	match parser(input) {
+		Ok(result) => Ok(result),
+		Err(nom::Err::Error(e)) | Err(nom::Err::Failure(e)) => Err(e),
+		Err(nom::Err::Incomplete(_)) => unreachable!(),
+	}
+}
+
+// skips the rest of a broken statement: everything up to and including the next `;` or
+// balanced `{}` block, stopping early at a `}` that closes the enclosing block
+fn recover(input: Input) -> Input {
+	let fragment = input.fragment();
+	let bytes = fragment.as_bytes();
+	let mut depth = 0;
+	let mut i = 0;
+	while i < bytes.len() {
+		match bytes[i] {
+			b'"' => {
+				i += fragment[i + 1..].find('"').map(|n| n + 1).unwrap_or(fragment.len() - i);
+			},
+			b'/' if bytes.get(i + 1) == Some(&b'/') => {
+				i += fragment[i..].find('\n').unwrap_or(fragment.len() - i);
+			},
+			b'/' if bytes.get(i + 1) == Some(&b'*') => {
+				i += fragment[i..].find("*/").map(|n| n + 1).unwrap_or(fragment.len() - i);
+			},
+			b';' if depth == 0 => {
+				i += 1;
+				break;
+			},
+			b'{' => {
+				depth += 1;
+			},
+			b'}' if depth == 0 => {
+				// a stray `}` at the top level would otherwise never be consumed
+				if i == 0 {
+					i += 1;
+				}
+				break;
+			},
+			b'}' => {
+				depth -= 1;
+				if depth == 0 {
+					i += 1;
+					break;
+				}
+			},
+			_ => {},
+		}
+		i += 1;
+	}
+	let (rest, _) = input.take_split(std::cmp::min(i, bytes.len()));
+	rest
+}
+
+pub fn parse(source: &str, path: &Path) -> Result<Component, Vec<Diagnostic>> {
+	let state = ParseState {
+		path: path.to_path_buf(),
+		diagnostics: RefCell::new(Vec::new()),
+	};
+
+	let mut input = Input::new_extra(source, &state);
+	let mut imports = Vec::new();
+	let mut props = Vec::new();
+	let mut root: Option<Element> = None;
+	loop {
+		input = skip_space(input).map(|(rest, _)| rest).unwrap_or(input);
+		if input.fragment().is_empty() {
+			break;
+		}
+
+		let e = match attempt(import, input) {
+			Ok((rest, import)) => { imports.push(import); input = rest; continue; },
+			Err(e) => e,
+		};
+		let e = match attempt(prop_decl, input) {
+			Ok((rest, decl)) => { props.push(decl); input = rest; continue; },
+			Err(other) => nom::error::ParseError::or(e, other),
+		};
+		let e = match attempt(element, input) {
+			Ok((rest, element)) => {
+				if root.is_some() {
+					state.error(element.span, "a component can only have one root element");
+				} else {
+					root = Some(element);
+				}
+				input = rest;
+				continue;
+			},
+			Err(other) => nom::error::ParseError::or(e, other),
+		};
+
+		state.syntax_error(e);
+		input = recover(input);
+	}
+
+	let component = if let Some(root) = root {
+		if root.condition.is_some() || root.repeater.is_some() {
+			state.error(root.span, "the root element cannot have a condition or a repeater");
+		}
+		Some(Component {
+			name: String::new(),
+			source: source.to_owned(),
+			props: collect_props(&state, props),
+			root,
+			import_decls: imports,
+			imports_map: HashMap::new(),
+			status: Cell::new(CompileStatus::Ready),
+			diagnostics: Vec::new(),
+		})
+	} else {
+		state.error(Span::new(source.len(), source.len()), "expected a root element");
+		None
+	};
+
+	let diagnostics = state.diagnostics.into_inner();
+	match component {
+		Some(mut component) => {
+			component.diagnostics = diagnostics;
+			Ok(component)
+		},
+		None => Err(diagnostics),
+	}
+}
+
+// runs `parser` and pairs its output with the span of the input it consumed
+fn spanned<'a, O, F>(mut parser: F) -> impl FnMut(Input<'a>) -> IResult<'a, (O, Span)>
+where
+	F: FnMut(Input<'a>) -> IResult<'a, O>,
+{
+	move |input: Input<'a>| {
+		let start = input.location_offset();
+		let (input, output) = parser(input)?;
+		Ok((input, (output, Span::new(start, input.location_offset()))))
+	}
 }
 
 #[derive(Debug)]
 pub struct Repeater {
 	pub index: Option<String>,
 	pub item: String,
-	pub collection: Value,
+	pub collection: Collection,
+	// keeps the element of each item across updates, e.g. `key (item.id)`
+	pub key: Option<Value>,
 }
 
 #[derive(Debug)]
@@ -98,9 +319,20 @@ pub struct Element {
 	pub path: Vec<String>,
 	pub data: Option<Value>,
 	pub condition: Option<Value>,
+	// the next branch of an `if`/`else` chain, which is shown when `condition` is false
+	pub otherwise: Option<Box<Element>>,
 	pub repeater: Option<Repeater>,
 	pub properties: HashMap<String, Value>,
+	// keyed by the dotted path of each property, e.g. `events.pointer.click`
+	pub property_spans: HashMap<String, Span>,
 	pub children: Vec<Content>,
+	pub span: Span,
+}
+
+impl Element {
+	pub fn property_span(&self, path: &str) -> Span {
+		self.property_spans.get(path).copied().unwrap_or(self.span)
+	}
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -113,14 +345,17 @@ pub enum CompileStatus {
 #[derive(Debug)]
 pub struct Component {
 	pub name: String,
+	pub source: String,
 	pub root: Element,
 	pub props: HashMap<String, PropDecl>,
 	pub import_decls: Vec<Import>,
 	pub imports_map: HashMap<String, std::path::PathBuf>,
 	pub status: Cell<CompileStatus>,
+	// syntax errors that were recovered from; reported together with the semantic checks
+	pub diagnostics: Vec<Diagnostic>,
 }
 
-fn import(input: &str) -> IResult<&str, Import> {
+fn import(input: Input) -> IResult<Import> {
 	map(
 		terminated(
 			pair(
@@ -139,105 +374,176 @@ fn import(input: &str) -> IResult<&str, Import> {
 			),
 			char(';')
 		),
-		|(path,alias)| Import { path: PathBuf::from(path), alias: alias.map(|e| e.to_owned()) }
+		|(path,alias)| Import { path: PathBuf::from(path.fragment()), alias: alias.map(|e| e.to_owned()) }
 	)
 	(input)
 }
 
-fn collect_properties(props: Vec<Property>) -> HashMap<String, Value> {
+fn collect_properties(state: &ParseState, props: Vec<Property>) -> (HashMap<String, Value>, HashMap<String, Span>) {
 	let mut props_map = HashMap::new();
+	let mut spans = HashMap::new();
 	for prop in props.into_iter() {
-		add_property(&mut props_map, &prop.path, prop.value);
+		let key = prop.path.join(".");
+		if add_property(&mut props_map, &prop.path, prop.value) {
+			spans.insert(key, prop.span);
+		} else {
+			state.error(prop.span, format!("tried to assign property `{}` more than once", key));
+		}
 	}
-	props_map
+	(props_map, spans)
 }
-fn add_property(map: &mut HashMap<String, Value>, path: &[String], value: Value) {
+fn add_property(map: &mut HashMap<String, Value>, path: &[String], value: Value) -> bool {
 	if path.len() == 1 {
 		if map.contains_key(&path[0]) {
-			eprintln!("tried to assign property `{}` more than once", path[0]);
-		} else {
-			map.insert(path[0].clone(), value);
+			return false;
 		}
+		map.insert(path[0].clone(), value);
+		true
 	} else if let Some(Value::Object(map)) = map.get_mut(&path[0]) {
-		add_property(map, &path[1..], value);
+		add_property(map, &path[1..], value)
 	} else {
 		let mut new_map = HashMap::new();
 		add_property(&mut new_map, &path[1..], value);
 		map.insert(path[0].clone(), Value::Object(new_map));
+		true
 	}
 }
 
-fn element(input: &str) -> IResult<&str, Element> {
-	let (input, (path, data, condition, repeater, (properties, children))) = tuple((
-		terminated(path, skip_space),
+fn element(input: Input) -> IResult<Element> {
+	let state = input.extra;
+	let (input, ((path, span), data, condition, repeater, open)) = tuple((
+		terminated(spanned(path), skip_space),
 		opt(binding),
 		opt(condition),
 		opt(repeater),
-		delimited(
-			pair(skip_space, char('{')),
-			tuple((
-				many0(delimited(skip_space, property, skip_space)),
-				many0(delimited(skip_space, content, skip_space)),
-			)),
-			pair(skip_space, char('}')),
-		)
+		preceded(skip_space, spanned(char('{'))),
 	))
 	(input)?;
+
+	let (input, (properties, children)) = element_body(input, open.1)?;
 	
-	let properties = collect_properties(properties);
+	let (properties, property_spans) = collect_properties(state, properties);
 
 	let path = path.into_iter().map(|e| e.to_owned()).collect();
 
-	let repeater = repeater.map(|(i, e, c)| Repeater {
+	let repeater = repeater.map(|(i, e, c, k)| Repeater {
 		index: i.map(|e| e.to_owned()),
 		item: e.to_owned(),
-		collection: c
+		collection: c,
+		key: k,
 	});
 
 	Ok((input, Element {
 		path,
 		data,
 		condition,
+		otherwise: None,
 		repeater,
 		properties,
+		property_spans,
 		children,
+		span,
 	}))
 }
 
-fn props(input: &str) -> IResult<&str, HashMap<String, PropDecl>> {
-	let (input, props) = many0(delimited(skip_space, prop_decl, skip_space))
-	(input)?;
-	let props = props.into_iter().fold(HashMap::new(), |mut map, e| {
+// parses the inside of an element block up to and including its closing `}`, reporting
+// and skipping over any statement that fails to parse
+fn element_body(mut input: Input, open: Span) -> IResult<(Vec<Property>, Vec<Content>)> {
+	let state = input.extra;
+	let mut properties = Vec::new();
+	let mut children = Vec::new();
+	loop {
+		input = skip_space(input)?.0;
+		if let Ok((rest, _)) = char::<_, SyntaxError>('}')(input) {
+			return Ok((rest, (properties, children)));
+		}
+		if input.fragment().is_empty() {
+			state.error(open, "unclosed `{`");
+			return Ok((input, (properties, children)));
+		}
+
+		let e = match attempt(property, input) {
+			Ok((rest, props)) => {
+				if !children.is_empty() {
+					for prop in props.iter() {
+						state.error(prop.span, "properties must be set before any child elements");
+					}
+				}
+				properties.extend(props);
+				input = rest;
+				continue;
+			},
+			Err(e) => e,
+		};
+		let e = match attempt(else_branch, input) {
+			Ok((rest, (branch, span))) => {
+				add_else_branch(state, &mut children, branch, span);
+				input = rest;
+				continue;
+			},
+			Err(other) => nom::error::ParseError::or(e, other),
+		};
+		let e = match attempt(content, input) {
+			Ok((rest, content)) => { children.push(content); input = rest; continue; },
+			Err(other) => nom::error::ParseError::or(e, other),
+		};
+
+		state.syntax_error(e);
+		input = recover(input);
+	}
+}
+
+// hangs a branch off the end of the `if` chain started by the element before it
+fn add_else_branch(state: &ParseState, children: &mut [Content], branch: Element, span: Span) {
+	let mut last = match children.last_mut() {
+		Some(Content::Element(e)) if e.condition.is_some() => e,
+		_ => {
+			state.error(span, "`else` must follow an element with an `if` condition");
+			return;
+		},
+	};
+	while last.otherwise.is_some() {
+		last = last.otherwise.as_mut().unwrap();
+	}
+	if last.condition.is_none() {
+		state.error(span, "this `if` chain already ended with an `else`");
+		return;
+	}
+	last.otherwise = Some(Box::new(branch));
+}
+
+fn collect_props(state: &ParseState, props: Vec<PropDecl>) -> HashMap<String, PropDecl> {
+	props.into_iter().fold(HashMap::new(), |mut map, e| {
 		if map.contains_key(&e.name) {
-			eprintln!("tried to decalre property `{}` more than once", e.name);
+			state.error(e.span, format!("tried to declare property `{}` more than once", e.name));
 		} else {
 			map.insert(e.name.clone(), e);
 		}
 		map
-	});
-
-	Ok((input, props))
+	})
 }
 
-fn prop_decl(input: &str) -> IResult<&str, PropDecl> {
-	let (input, ((is_pub, name), (prop_type, default))) = separated_pair(
+fn prop_decl(input: Input) -> IResult<PropDecl> {
+	let (input, (((is_pub, name), (prop_type, default)), span)) = spanned(separated_pair(
 		pair(
 			map(opt(terminated(tag("pub"), skip_space)), |e| e.is_some()),
 			name,
 		),
 		delimited(skip_space, char(':'), skip_space),
 		prop_type,
-	)
+	))
 	(input)?;
 	Ok((input, PropDecl {
 		is_pub,
 		name,
 		prop_type,
 		default,
+		span,
 	}))
 }
 
-fn prop_type(input: &str) -> IResult<&str, (Type, Option<Value>)> {
+fn prop_type(input: Input) -> IResult<(Type, Option<Value>)> {
+	let state = input.extra;
 	alt((
 		pair(
 			alt((
@@ -248,6 +554,7 @@ fn prop_type(input: &str) -> IResult<&str, (Type, Option<Value>)> {
 				map(tag("String"),    |_| Type::String),
 				map(tag("Boolean"),   |_| Type::Boolean),
 				map(tag("Alignment"), |_| Type::Alignment),
+				map(tag("Fit"),       |_| Type::Fit),
 				map(tag("Callback"),  |_| Type::Callback),
 				map(
 					delimited(
@@ -286,7 +593,7 @@ fn prop_type(input: &str) -> IResult<&str, (Type, Option<Value>)> {
 			|t| {
 				let map = t.into_iter().fold(HashMap::new(), |mut acc, decl| {
 					if acc.contains_key(&decl.name) {
-						eprintln!("tried to redeclare property `{}`", decl.name);
+						state.error(decl.span, format!("tried to redeclare property `{}`", decl.name));
 					} else {
 						acc.insert(decl.name, decl.prop_type);
 					}
@@ -299,20 +606,23 @@ fn prop_type(input: &str) -> IResult<&str, (Type, Option<Value>)> {
 	(input)
 }
 
-fn text_content(input: &str) -> IResult<&str, Element> {
-	let (input, result) = alt((
-		map(string, |e: &str| Value::String(e.to_owned())),
+fn text_content(input: Input) -> IResult<Element> {
+	let (input, (result, span)) = spanned(alt((
+		map(string, |e: Input| Value::String(e.fragment().to_string())),
 		binding
-	))
+	)))
 	(input)?;
 
 	Ok((input, Element {
 		path: vec!["text".to_owned()],
 		data: None,
 		condition: None,
+		otherwise: None,
 		repeater: None,
 		properties: hashmap!["content".to_owned() => result],
+		property_spans: hashmap!["content".to_owned() => span],
 		children: Vec::new(),
+		span,
 	}))
 }
 
@@ -320,12 +630,14 @@ fn text_content(input: &str) -> IResult<&str, Element> {
 struct Property {
 	path: Vec<String>,
 	value: Value,
+	span: Span,
 }
 
 #[derive(Debug, Clone)]
 pub struct Children {
 	pub single: bool,
 	pub filter: Option<Vec<String>>,
+	pub span: Span,
 }
 
 #[derive(Debug)]
@@ -334,7 +646,7 @@ pub enum Content {
 	Children(Children),
 }
 
-fn content(input: &str) -> IResult<&str, Content> {
+fn content(input: Input) -> IResult<Content> {
 	alt((
 		map(text_content, |e| Content::Element(e)),
 		map(element, |e| Content::Element(e)),
@@ -343,8 +655,8 @@ fn content(input: &str) -> IResult<&str, Content> {
 	(input)
 }
 
-fn children(input: &str) -> IResult<&str, Children> {
-	alt((
+fn children(input: Input) -> IResult<Children> {
+	let (input, ((single, filter), span)) = spanned(alt((
 		map(
 			delimited(
 				tuple((
@@ -359,7 +671,7 @@ fn children(input: &str) -> IResult<&str, Children> {
 					char(')'),
 				),
 			),
-			|filter| Children { single: true, filter: Some(filter) },
+			|filter| (true, Some(filter)),
 		),
 		map(
 			delimited(
@@ -375,21 +687,22 @@ fn children(input: &str) -> IResult<&str, Children> {
 					char(')'),
 				),
 			),
-			|filter| Children { single: false, filter: Some(filter) },
+			|filter| (false, Some(filter)),
 		),
 		map(
 			terminated(tag("@child"), not(alphanumeric1)),
-			|_| Children { single: true, filter: None },
+			|_| (true, None),
 		),
 		map(
 			terminated(tag("@children"), not(alphanumeric1)),
-			|_| Children { single: false, filter: None },
+			|_| (false, None),
 		),
-	))
-	(input)
+	)))
+	(input)?;
+	Ok((input, Children { single, filter, span }))
 }
 
-fn condition(input: &str) -> IResult<&str, Value> {
+fn condition(input: Input) -> IResult<Value> {
 	preceded(
 		terminated(tag("if"), skip_space),
 		terminated(binding, skip_space),
@@ -397,7 +710,19 @@ fn condition(input: &str) -> IResult<&str, Value> {
 	(input)
 }
 
-fn repeater(input: &str) -> IResult<&str, (Option<String>, String, Value)> {
+// `else rect { .. }`, or `else rect if (..) { .. }` to continue the chain
+fn else_branch(input: Input) -> IResult<(Element, Span)> {
+	map(
+		pair(
+			spanned(terminated(tag("else"), not(satisfy(|c| is_alphanumeric(c as u8) || c == '_')))),
+			preceded(skip_space, alt((text_content, element))),
+		),
+		|((_, span), branch)| (branch, span),
+	)
+	(input)
+}
+
+fn repeater(input: Input) -> IResult<(Option<String>, String, Collection, Option<Value>)> {
 	preceded(
 		terminated(tag("for"), skip_space),
 		tuple((
@@ -405,35 +730,100 @@ fn repeater(input: &str) -> IResult<&str, (Option<String>, String, Value)> {
 			terminated(name, skip_space),
 			preceded(
 				terminated(tag("in"), skip_space),
-				terminated(value, skip_space),
+				terminated(collection, skip_space),
 			),
+			opt(preceded(
+				terminated(tag("key"), skip_space),
+				terminated(binding, skip_space),
+			)),
 		))
 	)
 	(input)
 }
 
-fn property(input: &str) -> IResult<&str, Property> {
-	let (input, (path, value)) = alt((
-		terminated(
-			separated_pair(path, delimited(skip_space, char(':'), skip_space), value),
-			pair(
+fn collection(input: Input) -> IResult<Collection> {
+	alt((
+		range,
+		map(array, Collection::Array),
+		map(value, Collection::Value),
+	))
+	(input)
+}
+
+// `0..10`, `1..=(count)`, optionally followed by `step 2`
+fn range(input: Input) -> IResult<Collection> {
+	map(
+		tuple((
+			range_bound,
+			delimited(
 				skip_space,
 				alt((
-					char(';'),
-					peek(char('}')),
+					map(tag("..="), |_| true),
+					map(tag(".."), |_| false),
 				)),
-			)
+				skip_space,
+			),
+			range_bound,
+			opt(preceded(
+				delimited(skip_space, tag("step"), skip_space),
+				range_bound,
+			)),
+		)),
+		|(start, inclusive, end, step)| Collection::Range { start, end, inclusive, step },
+	)
+	(input)
+}
+
+fn range_bound(input: Input) -> IResult<Value> {
+	alt((
+		map(int, Value::Int),
+		binding,
+	))
+	(input)
+}
+
+fn array(input: Input) -> IResult<Vec<Value>> {
+	delimited(
+		pair(char('['), skip_space),
+		terminated(
+			separated_list0(delimited(skip_space, char(','), skip_space), value),
+			opt(preceded(skip_space, char(','))),
+		),
+		pair(skip_space, char(']')),
+	)
+	(input)
+}
+
+// an object block like `events: { pointer.click: (f); }` is flattened into one property per leaf
+fn property(input: Input) -> IResult<Vec<Property>> {
+	alt((
+		map(
+			separated_pair(path, delimited(skip_space, char(':'), skip_space), object_properties),
+			|(path, props)| {
+				props.into_iter().map(|mut prop| {
+					prop.path.splice(0..0, path.iter().cloned());
+					prop
+				}).collect()
+			},
+		),
+		map(
+			terminated(
+				spanned(separated_pair(path, delimited(skip_space, char(':'), skip_space), value)),
+				pair(
+					skip_space,
+					alt((
+						char(';'),
+						peek(char('}')),
+					)),
+				)
+			),
+			|((path, value), span)| vec![Property { path, value, span }],
 		),
-		separated_pair(path, delimited(skip_space, char(':'), skip_space), object),
 	))
-	(input)?;
-	Ok((input, Property {
-		path,
-		value,
-	}))
+	(input)
 }
 
-fn path(input: &str) -> IResult<&str, Vec<String>> {
+fn path(input: Input) -> IResult<Vec<String>> {
 	let (input, (first, mut rest)) = pair(
 		name,
 		many0(
@@ -445,7 +835,7 @@ fn path(input: &str) -> IResult<&str, Vec<String>> {
 	Ok((input, rest))
 }
 
-fn name(input: &str) -> IResult<&str, String> {
+fn name(input: Input) -> IResult<String> {
 	map(
 		// preceded(
 		// 	not(
@@ -461,30 +851,41 @@ fn name(input: &str) -> IResult<&str, String> {
 				),
 			),
 		// ),
-		|e: &str| e.to_owned()
+		|e: Input| e.fragment().to_string()
 	)
 	(input)
 }
 
-fn object(input: &str) -> IResult<&str, Value> {
+fn object_properties(input: Input) -> IResult<Vec<Property>> {
 	map(
 		delimited(
 			pair(char('{'), skip_space),
 			many1(delimited(skip_space, property, skip_space)),
 			pair(skip_space, char('}')),
 		),
-		|props| Value::Object(collect_properties(props)),
+		|props| props.into_iter().flatten().collect(),
 	)
 	(input)
 }
 
-fn value(input: &str) -> IResult<&str, Value> {
+fn object(input: Input) -> IResult<Value> {
+	let state = input.extra;
+	map(
+		object_properties,
+		|props| Value::Object(collect_properties(state, props).0),
+	)
+	(input)
+}
+
+fn value(input: Input) -> IResult<Value> {
 	alt((
-		px,
-		map(int, |e| Value::Int(e)),
+		length_sum,
+		length,
+		map(terminated(int, not(char('.'))), |e| Value::Int(e)),
 		map(float, |e| Value::Float(e)),
-		map(string, |e: &str| Value::String(e.to_owned())),
+		map(string, |e: Input| Value::String(e.fragment().to_string())),
 		color,
+		gradient,
 		boolean,
 		enum_value,
 		object,
@@ -493,7 +894,7 @@ fn value(input: &str) -> IResult<&str, Value> {
 	(input)
 }
 
-fn float(input: &str) -> IResult<&str, f32> {
+fn float(input: Input) -> IResult<f32> {
 	map(
 		recognize(
 			pair(
@@ -507,40 +908,87 @@ fn float(input: &str) -> IResult<&str, f32> {
 				)),
 			),
 		),
-		|e| { str::parse(e).unwrap() }
+		|e: Input| { str::parse(e.fragment()).unwrap() }
 	)
 	(input)
 }
 
-fn enum_value(input: &str) -> IResult<&str, Value> {
+fn enum_value(input: Input) -> IResult<Value> {
 	alt((
 		map(tag(".stretch"),    |_| Value::Alignment(Alignment::Stretch)),
 		map(tag(".center"),     |_| Value::Alignment(Alignment::Center)),
 		map(tag(".start"),      |_| Value::Alignment(Alignment::Start)),
 		map(tag(".end"),        |_| Value::Alignment(Alignment::End)),
+		map(tag(".contain"),    |_| Value::Fit(Fit::Contain)),
+		map(tag(".cover"),      |_| Value::Fit(Fit::Cover)),
+		map(tag(".fill"),       |_| Value::Fit(Fit::Fill)),
 	))
 	(input)
 }
 
-fn px(input: &str) -> IResult<&str, Value> {
-	terminated(
-		map(float, |e| Value::Px(e)),
-		tag("px"),
+// `50% - 10px`: like css `calc()`, a sum of length literals doesn't need to be wrapped in parentheses
+fn length_sum(input: Input) -> IResult<Value> {
+	map(
+		spanned(
+			verify(
+				binary_expr(map(length, |e| Expr::Literal(Box::new(e))), additive_op),
+				|e: &Expr| matches!(e, Expr::Binary(..)),
+			),
+		),
+		|(e, span)| Value::Binding(e, span),
 	)
 	(input)
 }
 
-fn int(input: &str) -> IResult<&str, i32> {
+// `10px`, `2.5cm`, `50%`, `1.5em`, `100vh`, ...
+fn length(input: Input) -> IResult<Value> {
 	map(
-		recognize(
-			pair(opt(one_of("+-")), digit1),
+		pair(
+			float,
+			alt((
+				map(tag("px"), |_| None),
+				map(tag("in"), |_| Some(Unit::In)),
+				map(tag("cm"), |_| Some(Unit::Cm)),
+				map(tag("mm"), |_| Some(Unit::Mm)),
+				map(tag("pt"), |_| Some(Unit::Pt)),
+				map(tag("em"), |_| Some(Unit::Em)),
+				map(tag("vw"), |_| Some(Unit::Vw)),
+				map(tag("vh"), |_| Some(Unit::Vh)),
+				// a `%` followed by an operand is the remainder operator
+				map(
+					terminated(
+						char('%'),
+						not(preceded(skip_space, satisfy(|c| c.is_alphanumeric() || "_(.".contains(c)))),
+					),
+					|_| Some(Unit::Percent),
+				),
+			)),
 		),
-		|e| { str::parse(e).unwrap() }
+		|(n, unit)| match unit {
+			Some(unit) => Value::Length(n, unit),
+			None => Value::Px(n),
+		},
 	)
 	(input)
 }
 
-fn string(input: &str) -> IResult<&str, &str> {
+// an integer too large for an `Int` fails outright rather than being parsed as some other
+// kind of number, unless it has a fraction and so is a float
+fn int(input: Input) -> IResult<i32> {
+	let (rest, digits) = recognize(pair(opt(one_of("+-")), digit1))(input)?;
+	match str::parse(digits.fragment()) {
+		Ok(n) => Ok((rest, n)),
+		Err(_) if pair(char::<Input, SyntaxError>('.'), digit1)(rest).is_ok() => {
+			Err(nom::Err::Error(SyntaxError { input, expected: Vec::new() }))
+		},
+		Err(_) => Err(nom::Err::Failure(SyntaxError {
+			input,
+			expected: vec![format!("an integer from {} to {}", i32::MIN, i32::MAX)],
+		})),
+	}
+}
+
+fn string(input: Input) -> IResult<Input> {
 	delimited(
 		char('"'),
 		recognize(many0(satisfy(|c| c != '"'))),
@@ -549,9 +997,19 @@ fn string(input: &str) -> IResult<&str, &str> {
 	(input)
 }
 
-fn color(input: &str) -> IResult<&str, Value> {
-	let (input, value) = preceded(
-		char('#'), 
+// `#f00`, `#ff000080`, `rgb(255, 0, 0)`, `hsl(0 100% 50% / 0.5)` or a css color name like `red`
+fn color(input: Input) -> IResult<Value> {
+	alt((
+		hex_color,
+		color_function,
+		named_color,
+	))
+	(input)
+}
+
+fn hex_color(input: Input) -> IResult<Value> {
+	preceded(
+		char('#'),
 		map(
 			terminated(
 				recognize(
@@ -559,19 +1017,168 @@ fn color(input: &str) -> IResult<&str, Value> {
 						many1_count(
 							satisfy(|c| is_hex_digit(c as u8))
 						),
-						|&n| n == 3 || n == 6
+						|&n| matches!(n, 3 | 4 | 6 | 8)
 					),
 				),
 				not(alphanumeric1),
 			),
-			|e| Value::color_from_hex(e)
+			|e: Input| Value::color_from_hex(e.fragment())
 		)
 	)
-	(input)?;
-	Ok((input, value))
+	(input)
+}
+
+fn named_color(input: Input) -> IResult<Value> {
+	map_opt(
+		name,
+		|name| colors::named_color(&name).map(|(r, g, b, a)| Value::Color(r, g, b, a)),
+	)
+	(input)
+}
+
+fn color_function(input: Input) -> IResult<Value> {
+	alt((
+		map(
+			color_args(alt((tag("rgba"), tag("rgb"))), rgb_channel, rgb_channel, rgb_channel),
+			|(r, g, b, a)| Value::Color(r, g, b, a.unwrap_or(1.0)),
+		),
+		map(
+			color_args(alt((tag("hsla"), tag("hsl"))), hue, percentage, percentage),
+			|(h, s, l, a)| {
+				let (r, g, b) = colors::hsl_to_rgb(h, s, l);
+				Value::Color(r, g, b, a.unwrap_or(1.0))
+			},
+		),
+	))
+	(input)
+}
+
+// the arguments of a color function, separated by commas or, as in newer css, by spaces with
+// the alpha after a `/`
+fn color_args<'a, N, A, B, C, FA, FB, FC>(
+	name: N,
+	first: FA,
+	second: FB,
+	third: FC,
+) -> impl FnMut(Input<'a>) -> IResult<'a, (A, B, C, Option<f32>)>
+where
+	N: FnMut(Input<'a>) -> IResult<'a, Input<'a>>,
+	FA: FnMut(Input<'a>) -> IResult<'a, A>,
+	FB: FnMut(Input<'a>) -> IResult<'a, B>,
+	FC: FnMut(Input<'a>) -> IResult<'a, C>,
+{
+	let separator = || alt((
+		recognize(delimited(skip_space, char(','), skip_space)),
+		multispace1,
+	));
+	delimited(
+		pair(name, pair(skip_space, char('('))),
+		delimited(
+			skip_space,
+			tuple((
+				first,
+				preceded(separator(), second),
+				preceded(separator(), third),
+				opt(preceded(delimited(skip_space, one_of(",/"), skip_space), alpha)),
+			)),
+			skip_space,
+		),
+		char(')'),
+	)
+}
+
+// 0 to 255, or a percentage
+fn rgb_channel(input: Input) -> IResult<u8> {
+	map(
+		pair(float, opt(char('%'))),
+		|(n, percent)| {
+			let n = if percent.is_some() { n / 100.0 * 255.0 } else { n };
+			n.round().clamp(0.0, 255.0) as u8
+		},
+	)
+	(input)
 }
 
-fn boolean(input: &str) -> IResult<&str, Value> {
+// 0 to 1, or a percentage
+fn alpha(input: Input) -> IResult<f32> {
+	map(
+		pair(float, opt(char('%'))),
+		|(n, percent)| {
+			let n = if percent.is_some() { n / 100.0 } else { n };
+			n.clamp(0.0, 1.0)
+		},
+	)
+	(input)
+}
+
+// in degrees
+fn hue(input: Input) -> IResult<f32> {
+	terminated(float, opt(tag("deg")))
+	(input)
+}
+
+// as a fraction
+fn percentage(input: Input) -> IResult<f32> {
+	map(terminated(float, char('%')), |n| n / 100.0)
+	(input)
+}
+
+// `#f00` or `#00f 80%`
+fn color_stop(input: Input) -> IResult<ColorStop> {
+	map(
+		pair(
+			color,
+			opt(preceded(skip_space, terminated(float, char('%')))),
+		),
+		|(color, position)| {
+			let color = match color {
+				Value::Color(r, g, b, a) => (r, g, b, a),
+				_ => unreachable!(),
+			};
+			ColorStop { color, position: position.map(|p| p / 100.0) }
+		},
+	)
+	(input)
+}
+
+fn color_stops(input: Input) -> IResult<Vec<ColorStop>> {
+	verify(
+		separated_list1(
+			char(','),
+			delimited(skip_space, color_stop, skip_space),
+		),
+		|stops: &Vec<ColorStop>| stops.len() >= 2,
+	)
+	(input)
+}
+
+fn gradient(input: Input) -> IResult<Value> {
+	alt((
+		map(
+			delimited(
+				pair(tag("linear"), pair(skip_space, char('('))),
+				separated_pair(
+					delimited(skip_space, terminated(float, tag("deg")), skip_space),
+					char(','),
+					color_stops,
+				),
+				char(')'),
+			),
+			|(angle, stops)| Value::Gradient(Gradient::Linear(angle, stops)),
+		),
+		map(
+			delimited(
+				pair(tag("radial"), pair(skip_space, char('('))),
+				color_stops,
+				char(')'),
+			),
+			|stops| Value::Gradient(Gradient::Radial(stops)),
+		),
+	))
+	(input)
+}
+
+fn boolean(input: Input) -> IResult<Value> {
 	terminated(
 		alt((
 			map(tag("true"), |_| Value::Boolean(true)),
@@ -582,7 +1189,7 @@ fn boolean(input: &str) -> IResult<&str, Value> {
 	(input)
 }
 
-fn expr(input: &str) -> IResult<&str, Expr> {
+fn path_expr(input: Input) -> IResult<Expr> {
 	map(
 		path,
 		|v| {
@@ -599,16 +1206,191 @@ fn expr(input: &str) -> IResult<&str, Expr> {
 	(input)
 }
 
-fn binding(input: &str) -> IResult<&str, Value> {
-	delimited(
-		pair(char('('), skip_space),
-		map(expr, |e| Value::Binding(e)),
-		pair(skip_space, char(')')),
+fn literal_expr(input: Input) -> IResult<Expr> {
+	map(
+		alt((
+			length,
+			map(terminated(int, not(char('.'))), |e| Value::Int(e)),
+			map(float, |e| Value::Float(e)),
+			map(string, |e: Input| Value::String(e.fragment().to_string())),
+			// color names are paths here, see `check_expr`
+			hex_color,
+			color_function,
+			boolean,
+			enum_value,
+		)),
+		|e| Expr::Literal(Box::new(e)),
+	)
+	(input)
+}
+
+fn call_expr(input: Input) -> IResult<Expr> {
+	map(
+		pair(
+			terminated(name, pair(skip_space, char('('))),
+			terminated(
+				separated_list0(
+					char(','),
+					delimited(skip_space, expr, skip_space),
+				),
+				pair(skip_space, char(')')),
+			),
+		),
+		|(name, args)| Expr::Call(name, args),
 	)
 	(input)
 }
 
-fn multiline_comment(input: &str) -> IResult<&str, &str> {
+fn primary_expr(input: Input) -> IResult<Expr> {
+	alt((
+		delimited(
+			pair(char('('), skip_space),
+			expr,
+			pair(skip_space, char(')')),
+		),
+		literal_expr,
+		call_expr,
+		path_expr,
+	))
+	(input)
+}
+
+fn unary_expr(input: Input) -> IResult<Expr> {
+	expected("expression", alt((
+		map(
+			pair(
+				terminated(
+					alt((
+						map(char('!'), |_| UnaryOp::Not),
+						map(char('-'), |_| UnaryOp::Neg),
+					)),
+					skip_space,
+				),
+				unary_expr,
+			),
+			|(op, e)| Expr::Unary(op, Box::new(e)),
+		),
+		primary_expr,
+	)))
+	(input)
+}
+
+fn binary_expr<'a, O, P>(mut operand: O, mut operator: P) -> impl FnMut(Input<'a>) -> IResult<'a, Expr>
+where
+	O: FnMut(Input<'a>) -> IResult<'a, Expr>,
+	P: FnMut(Input<'a>) -> IResult<'a, BinaryOp>,
+{
+	move |input| {
+		let (mut input, mut lhs) = operand(input)?;
+		loop {
+			let op = preceded(skip_space, &mut operator)(input);
+			let (rest, op) = match op {
+				Ok(result) => result,
+				Err(nom::Err::Error(_)) => return Ok((input, lhs)),
+				Err(e) => return Err(e),
+			};
+			let (rest, rhs) = preceded(skip_space, &mut operand)(rest)?;
+			lhs = Expr::Binary(op, Box::new(lhs), Box::new(rhs));
+			input = rest;
+		}
+	}
+}
+
+fn multiplicative_expr(input: Input) -> IResult<Expr> {
+	binary_expr(
+		unary_expr,
+		alt((
+			map(char('*'), |_| BinaryOp::Mul),
+			map(char('/'), |_| BinaryOp::Div),
+			map(char('%'), |_| BinaryOp::Rem),
+		)),
+	)
+	(input)
+}
+
+fn additive_op(input: Input) -> IResult<BinaryOp> {
+	alt((
+		map(char('+'), |_| BinaryOp::Add),
+		map(char('-'), |_| BinaryOp::Sub),
+	))
+	(input)
+}
+
+fn additive_expr(input: Input) -> IResult<Expr> {
+	binary_expr(multiplicative_expr, additive_op)
+	(input)
+}
+
+fn comparison_expr(input: Input) -> IResult<Expr> {
+	binary_expr(
+		additive_expr,
+		alt((
+			map(tag("<="), |_| BinaryOp::Le),
+			map(tag(">="), |_| BinaryOp::Ge),
+			map(char('<'), |_| BinaryOp::Lt),
+			map(char('>'), |_| BinaryOp::Gt),
+		)),
+	)
+	(input)
+}
+
+fn equality_expr(input: Input) -> IResult<Expr> {
+	binary_expr(
+		comparison_expr,
+		alt((
+			map(tag("=="), |_| BinaryOp::Eq),
+			map(tag("!="), |_| BinaryOp::Ne),
+		)),
+	)
+	(input)
+}
+
+fn and_expr(input: Input) -> IResult<Expr> {
+	binary_expr(equality_expr, map(tag("&&"), |_| BinaryOp::And))
+	(input)
+}
+
+fn or_expr(input: Input) -> IResult<Expr> {
+	binary_expr(and_expr, map(tag("||"), |_| BinaryOp::Or))
+	(input)
+}
+
+fn expr(input: Input) -> IResult<Expr> {
+	let (input, condition) = or_expr(input)?;
+	let (input, branches) = opt(
+		pair(
+			preceded(
+				delimited(skip_space, char('?'), skip_space),
+				expr,
+			),
+			preceded(
+				delimited(skip_space, char(':'), skip_space),
+				expr,
+			),
+		)
+	)
+	(input)?;
+
+	if let Some((then, otherwise)) = branches {
+		Ok((input, Expr::Ternary(Box::new(condition), Box::new(then), Box::new(otherwise))))
+	} else {
+		Ok((input, condition))
+	}
+}
+
+fn binding(input: Input) -> IResult<Value> {
+	map(
+		delimited(
+			pair(char('('), skip_space),
+			spanned(expr),
+			pair(skip_space, char(')')),
+		),
+		|(e, span)| Value::Binding(e, span),
+	)
+	(input)
+}
+
+fn multiline_comment(input: Input) -> IResult<Input> {
 	recognize(
 		delimited(
 			tag("/*"),
@@ -627,7 +1409,7 @@ fn multiline_comment(input: &str) -> IResult<&str, &str> {
 	(input)
 }
 
-fn line_comment(input: &str) -> IResult<&str, &str> {
+fn line_comment(input: Input) -> IResult<Input> {
 	recognize(
 		preceded(
 			tag("//"),
@@ -637,12 +1419,181 @@ fn line_comment(input: &str) -> IResult<&str, &str> {
 	(input)
 }
 
-fn comment(input: &str) -> IResult<&str, &str> {
+fn comment(input: Input) -> IResult<Input> {
 	alt((line_comment, multiline_comment))
 	(input)
 }
 
-fn skip_space(input: &str) -> IResult<&str, &str> {
+fn skip_space(input: Input) -> IResult<Input> {
 	recognize(many0(alt((multispace1, comment))))
 	(input)
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	// the messages of every error `source` produces, and where each starts
+	fn errors(source: &str) -> Vec<(String, usize)> {
+		let diagnostics = match parse(source, Path::new("test.ui")) {
+			Ok(component) => component.diagnostics,
+			Err(diagnostics) => diagnostics,
+		};
+		diagnostics.into_iter().map(|e| (e.message, e.span.start)).collect()
+	}
+
+	// the channels of the color `source` parses to, if it parses completely
+	fn parse_color(source: &str) -> Option<(u8, u8, u8, f32)> {
+		let state = ParseState {
+			path: PathBuf::from("test.ui"),
+			diagnostics: RefCell::new(Vec::new()),
+		};
+		match color(Input::new_extra(source, &state)) {
+			Ok((rest, Value::Color(r, g, b, a))) if rest.fragment().is_empty() => Some((r, g, b, a)),
+			_ => None,
+		}
+	}
+
+	#[test]
+	fn parses_hex_colors() {
+		assert_eq!(parse_color("#f00"), Some((255, 0, 0, 1.0)));
+		assert_eq!(parse_color("#f008"), Some((255, 0, 0, 136.0 / 255.0)));
+		assert_eq!(parse_color("#12aBcD"), Some((0x12, 0xab, 0xcd, 1.0)));
+		assert_eq!(parse_color("#12abcd00"), Some((0x12, 0xab, 0xcd, 0.0)));
+		assert_eq!(parse_color("#12345"), None);
+		assert_eq!(parse_color("#ffg"), None);
+	}
+
+	#[test]
+	fn parses_rgb_colors() {
+		assert_eq!(parse_color("rgb(255, 128, 0)"), Some((255, 128, 0, 1.0)));
+		assert_eq!(parse_color("rgba(0,0,0,0.5)"), Some((0, 0, 0, 0.5)));
+		assert_eq!(parse_color("rgb(100% 50% 0% / 25%)"), Some((255, 128, 0, 0.25)));
+		assert_eq!(parse_color("rgb(300, -5, 0, 2)"), Some((255, 0, 0, 1.0)));
+		assert_eq!(parse_color("rgb(1, 2)"), None);
+	}
+
+	#[test]
+	fn parses_hsl_colors() {
+		assert_eq!(parse_color("hsl(0, 100%, 50%)"), Some((255, 0, 0, 1.0)));
+		assert_eq!(parse_color("hsl(120deg 100% 25%)"), Some((0, 128, 0, 1.0)));
+		assert_eq!(parse_color("hsla(240, 100%, 50%, 0.5)"), Some((0, 0, 255, 0.5)));
+		assert_eq!(parse_color("hsl(0, 0%, 100%)"), Some((255, 255, 255, 1.0)));
+		assert_eq!(parse_color("hsl(0, 100, 50)"), None);
+	}
+
+	#[test]
+	fn parses_named_colors() {
+		assert_eq!(parse_color("rebeccapurple"), Some((0x66, 0x33, 0x99, 1.0)));
+		assert_eq!(parse_color("transparent"), Some((0, 0, 0, 0.0)));
+		assert_eq!(parse_color("notacolor"), None);
+	}
+
+	#[test]
+	fn reports_every_broken_property() {
+		let source = "rect {\n\twidth: 10px;\n\theight: ;\n\tradius: 2px;\n\tx: 1px 2px;\n}";
+		let errors = errors(source);
+		assert_eq!(errors.len(), 2, "{:?}", errors);
+		assert_eq!(errors[0], (String::from("expected one of `{`, `.`, `\"`, `#`, `(`, found `;`"), source.find(": ;").unwrap() + 2));
+		assert_eq!(errors[1], (String::from("expected one of `;`, `}`, found `2px`"), source.rfind("2px").unwrap()));
+	}
+
+	#[test]
+	fn keeps_the_statements_around_an_error() {
+		let component = parse("count: Int;\nrect {\n\twidth: 10px;\n\theight: ;\n\tradius: 2px;\n}", Path::new("test.ui")).unwrap();
+		assert_eq!(component.diagnostics.len(), 1);
+		assert!(component.props.contains_key("count"));
+		assert!(component.root.properties.contains_key("width"));
+		assert!(component.root.properties.contains_key("radius"));
+		assert!(!component.root.properties.contains_key("height"));
+	}
+
+	#[test]
+	fn recovers_inside_nested_elements() {
+		let source = "rect {\n\trect { width: ?; }\n\ty: ;\n\trect { x: 1px; }\n}";
+		let errors = errors(source);
+		assert_eq!(errors.len(), 2, "{:?}", errors);
+		assert_eq!(errors[0].1, source.find('?').unwrap());
+		assert_eq!(errors[1].1, source.find("y: ;").unwrap() + 3);
+		let component = parse(source, Path::new("test.ui")).unwrap();
+		assert_eq!(component.root.children.len(), 2);
+	}
+
+	#[test]
+	fn reports_errors_at_the_top_level_and_a_missing_root() {
+		let errors = errors("count: ;\nimport \"a.ui\"\n");
+		assert_eq!(errors.len(), 3, "{:?}", errors);
+		assert_eq!(errors[2].0, "expected a root element");
+	}
+
+	#[test]
+	fn reports_an_unclosed_block_where_it_opens() {
+		let errors = errors("rect {\n\twidth: 10px;\n");
+		assert_eq!(errors, vec![(String::from("unclosed `{`"), 5)]);
+	}
+
+	// `source` parsed as an expression, written out with every operation in prefix form
+	fn parse_expr(source: &str) -> String {
+		fn show(e: &Expr) -> String {
+			match e {
+				Expr::Path(path, Ctx::Parent) => format!("parent.{}", path.join(".")),
+				Expr::Path(path, _) => path.join("."),
+				Expr::Literal(value) => match value.as_ref() {
+					Value::Int(n) => n.to_string(),
+					Value::Float(n) => format!("{n:?}"),
+					Value::String(s) => format!("{s:?}"),
+					value => format!("{value:?}"),
+				},
+				Expr::Unary(op, operand) => format!("({} {})", op.symbol(), show(operand)),
+				Expr::Binary(op, lhs, rhs) => format!("({} {} {})", op.symbol(), show(lhs), show(rhs)),
+				Expr::Ternary(c, then, otherwise) => format!("(? {} {} {})", show(c), show(then), show(otherwise)),
+				Expr::Call(name, args) => format!("{name}({})", args.iter().map(show).collect::<Vec<_>>().join(", ")),
+				Expr::Cast(operand, _) => show(operand),
+			}
+		}
+		let state = ParseState {
+			path: PathBuf::from("test.ui"),
+			diagnostics: RefCell::new(Vec::new()),
+		};
+		match expr(Input::new_extra(source, &state)) {
+			Ok((rest, e)) if rest.fragment().is_empty() => show(&e),
+			_ => panic!("`{source}` didn't parse as an expression"),
+		}
+	}
+
+	#[test]
+	fn binary_operators_bind_by_precedence() {
+		assert_eq!(parse_expr("a + b * c"), "(+ a (* b c))");
+		assert_eq!(parse_expr("a - b - c"), "(- (- a b) c)");
+		assert_eq!(parse_expr("a / b % c * d"), "(* (% (/ a b) c) d)");
+		assert_eq!(parse_expr("-(a + b) % 2"), "(% (- (+ a b)) 2)");
+		assert_eq!(parse_expr("!a && b || c == d < e + 1"), "(|| (&& (! a) b) (== c (< d (+ e 1))))");
+		assert_eq!(parse_expr("a<=b != c>=d"), "(!= (<= a b) (>= c d))");
+	}
+
+	#[test]
+	fn ternaries_nest_to_the_right() {
+		assert_eq!(parse_expr("a ? b : c"), "(? a b c)");
+		assert_eq!(parse_expr("a ? b : c ? d : e"), "(? a b (? c d e))");
+		assert_eq!(parse_expr("a || b ? x + 1 : 2.5"), "(? (|| a b) (+ x 1) 2.5)");
+		assert_eq!(parse_expr("a ? (b ? c : d) : e"), "(? a (? b c d) e)");
+	}
+
+	#[test]
+	fn parses_calls_and_paths() {
+		assert_eq!(parse_expr("max(a, b + 1)"), "max(a, (+ b 1))");
+		assert_eq!(parse_expr("floor( x / 2 )"), "floor((/ x 2))");
+		assert_eq!(parse_expr("len(items) > 0"), "(> len(items) 0)");
+		assert_eq!(parse_expr("parent.width - item.size"), "(- parent.width item.size)");
+		assert_eq!(parse_expr("\"n: \" + n"), "(+ \"n: \" n)");
+	}
+
+	#[test]
+	fn reports_integers_too_large_for_an_int() {
+		let source = "n: Int;\nspan { (n + 99999999999) }";
+		let errors = errors(source);
+		let expected = format!("expected an integer from {} to {}, found `99999999999`", i32::MIN, i32::MAX);
+		assert_eq!(errors, vec![(expected, source.find("999").unwrap())]);
+		assert_eq!(parse_expr("3000000000.5"), "3000000000.0");
+		assert_eq!(parse_expr("2147483647"), "2147483647");
+	}
+}
@@ -5,7 +5,6 @@ use std::process;
 
 #[derive(Default)]
 struct Options {
-	exe: String,
 	file: String,
 	watch: bool,
 	web: bool,
@@ -48,7 +47,6 @@ fn process_args() -> Options {
 	}
 
 	Options {
-		exe,
 		file: file.unwrap(),
 		watch: watch.unwrap_or_default(),
 		web: web.unwrap_or_default(),
@@ -58,8 +56,9 @@ fn process_args() -> Options {
 fn main() {
 	let options = process_args();
 	if options.watch {
-		watch(&options.exe, &options.file, options.web);
-	} else if let Err(message) = build(&options.exe, &options.file, options.web) {
-		eprintln!("{}", message)
+		watch_ui(&options.file, options.web);
+	} else if let Err(message) = build_ui(&options.file, options.web) {
+		eprintln!("{}", message);
+		process::exit(1);
 	}
 }
\ No newline at end of file
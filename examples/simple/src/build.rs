@@ -1 +1,6 @@
-fn main() { ui_compiler::build("ui", "src/simple.ui", true).unwrap(); }
\ No newline at end of file
+fn main() {
+	if let Err(message) = ui_compiler::build_ui("src/simple.ui", true) {
+		eprintln!("{}", message);
+		std::process::exit(1);
+	}
+}
\ No newline at end of file
@@ -3,6 +3,7 @@ include!("./dist/simple.rs");
 impl ui::DefaultProps for Props {
     fn default() -> Props {
         Props {
+            title: "hello world".to_owned(),
             toggle_show: Callback::from(&Simple::toggle_show),
             ..Default::default()
         }
@@ -10,17 +11,14 @@ impl ui::DefaultProps for Props {
 }
 
 impl ui::Component for Simple {
-    fn on_init(&mut self) {
-        self.state = 420;
-    }
     fn on_update(&mut self) {
-        self.state += 1;
+        self.set_state(self.state + 1);
     }
 }
 
 impl Simple {
     fn toggle_show(&mut self) {
-        self.show = !self.show;
-        self.x = ui::Length::Px(self.x.to_px() + 4.0);
+        self.set_show(!self.show);
+        self.set_x(ui::Length::Px(self.x.to_px() + 4.0));
     }
 }
\ No newline at end of file